@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use aoc::rng::Lcg;
+use aoc::solutions::day03;
+
+// A square schematic with numbers of one to three digits and symbols scattered
+// at pseudo-random, with about the same density as the real input.
+fn synthetic_schematic(size: usize) -> String {
+    const SYMBOLS: &[u8] = b"*#+$/@=%&-";
+    let mut rng = Lcg::new(0x9e3779b97f4a7c15);
+
+    let mut out = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        let mut line = Vec::with_capacity(size);
+        while line.len() < size {
+            match rng.below(20) {
+                0..=2 => line.extend((0..1 + rng.below(3)).map(|_| b'0' + rng.below(10) as u8)),
+                3 => line.push(SYMBOLS[rng.below(SYMBOLS.len())]),
+                _ => line.push(b'.'),
+            }
+        }
+        line.truncate(size);
+        out.push_str(std::str::from_utf8(&line).unwrap());
+        out.push('\n');
+    }
+    out
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Day 3 - synthetic schematics");
+    group.sample_size(10);
+    for size in [140, 1_000, 3_000] {
+        let input = synthetic_schematic(size);
+        group.bench_with_input(BenchmarkId::new("Part 1", size), &input, |b, input| {
+            b.iter(|| day03::part_one(input))
+        });
+        group.bench_with_input(BenchmarkId::new("Part 2", size), &input, |b, input| {
+            b.iter(|| day03::part_two(input))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use aoc::solutions::day09;
+use aoc::read_file;
+
+// The previous day 9 solution: every history keeps all of its difference
+// levels, each level is a new Vec, and all histories are stepped in lockstep.
+mod levels {
+    struct History {
+        sequences: Vec<Vec<i64>>,
+        complete: bool
+    }
+
+    impl History {
+        fn step(&mut self) {
+            let new_seq = self.sequences.last().unwrap()
+                .windows(2)
+                .map(|w| w[1] - w[0])
+                .collect::<Vec<_>>();
+            self.complete = new_seq.iter().all(|&x| x == 0);
+            self.sequences.push(new_seq);
+        }
+
+        fn predict_right(&self) -> i64 {
+            self.sequences.iter().rev().skip(1).map(|seq| seq[seq.len() - 1]).sum()
+        }
+    }
+
+    pub fn part_one(input: &str) -> i64 {
+        let mut histories = input.lines()
+            .map(|line| line.split_whitespace().map(|s| s.parse().unwrap()).collect())
+            .map(|seq| History { sequences: vec![seq], complete: false })
+            .collect::<Vec<History>>();
+
+        while histories.iter().any(|history| !history.complete) {
+            for history in histories.iter_mut() {
+                if !history.complete {
+                    history.step();
+                }
+            }
+        }
+
+        histories.iter().map(History::predict_right).sum()
+    }
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let input = read_file("inputs", 9);
+    assert_eq!(levels::part_one(&input), day09::part_one(&input));
+
+    let mut group = c.benchmark_group("Day 9 - differencing");
+    group.bench_function("all levels", |b| b.iter(|| levels::part_one(&input)));
+    group.bench_function("boundary values", |b| b.iter(|| day09::part_one(&input)));
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use aoc::rng::Lcg;
+use aoc::solutions::day11;
+
+// A square universe with `galaxies` galaxies at pseudo-random positions.
+// Every tenth row and column is left empty so there is something to expand.
+fn synthetic_universe(size: usize, galaxies: usize) -> String {
+    let mut grid = vec![vec!['.'; size]; size];
+    let mut rng = Lcg::new(0x2545f4914f6cdd1d);
+    let mut placed = 0;
+    while placed < galaxies {
+        let (row, col) = (rng.below(size), rng.below(size));
+        if row % 10 == 0 || col % 10 == 0 || grid[row][col] == '#' {
+            continue;
+        }
+        grid[row][col] = '#';
+        placed += 1;
+    }
+    grid.iter().map(|row| row.iter().collect::<String>() + "\n").collect()
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Day 11 - synthetic universes");
+    group.sample_size(10);
+    for (size, galaxies) in [(500, 10_000), (1_000, 40_000), (2_000, 100_000)] {
+        let input = synthetic_universe(size, galaxies);
+        group.bench_with_input(BenchmarkId::from_parameter(galaxies), &input, |b, input| {
+            b.iter(|| day11::part_two(input))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,409 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign};
+
+use bitvec::prelude::*;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A grid of booleans with one bit per cell.
+/// Every row starts on a fresh `u64`, so rows can be compared word by word.
+/// Bits past the last column are always zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    bits: BitVec<u64, Lsb0>,
+    rows: usize,
+    cols: usize,
+    words_per_row: usize,
+}
+
+impl BitGrid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words_per_row = cols.div_ceil(WORD_BITS);
+        BitGrid {
+            bits: BitVec::repeat(false, rows * words_per_row * WORD_BITS),
+            rows,
+            cols,
+            words_per_row,
+        }
+    }
+
+    /// Parses one row per line, cells equal to `on` are set.
+    /// Panics if the lines are not all of the same length.
+    pub fn parse(input: &str, on: char) -> Self {
+        let lines = input.lines().collect::<Vec<_>>();
+        let cols = lines.first().map_or(0, |line| line.chars().count());
+        let mut grid = BitGrid::new(lines.len(), cols);
+        for (r, line) in lines.iter().enumerate() {
+            assert_eq!(line.chars().count(), cols, "rows differ in length");
+            for (c, ch) in line.chars().enumerate() {
+                grid.set(r, c, ch == on);
+            }
+        }
+        grid
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn offset(&self, r: usize) -> usize {
+        r * self.words_per_row * WORD_BITS
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        assert!(r < self.rows && c < self.cols, "index out of bounds");
+        self.bits[self.offset(r) + c]
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, value: bool) {
+        assert!(r < self.rows && c < self.cols, "index out of bounds");
+        let i = self.offset(r) + c;
+        self.bits.set(i, value);
+    }
+
+    pub fn row(&self, r: usize) -> &BitSlice<u64, Lsb0> {
+        let start = self.offset(r);
+        &self.bits[start..start + self.cols]
+    }
+
+    pub fn row_mut(&mut self, r: usize) -> &mut BitSlice<u64, Lsb0> {
+        let start = self.offset(r);
+        &mut self.bits[start..start + self.cols]
+    }
+
+    /// The raw words of a row, padding bits included.
+    pub fn row_words(&self, r: usize) -> &[u64] {
+        let start = r * self.words_per_row;
+        &self.bits.as_raw_slice()[start..start + self.words_per_row]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.as_raw_slice().iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn row_count_ones(&self, r: usize) -> usize {
+        self.row_words(r).iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn col_count_ones(&self, c: usize) -> usize {
+        assert!(c < self.cols, "index out of bounds");
+        let (word, mask) = (c / WORD_BITS, 1u64 << (c % WORD_BITS));
+        // a grid without rows has no words at all
+        self.bits.as_raw_slice()
+            .get(word..)
+            .unwrap_or_default()
+            .iter()
+            .step_by(self.words_per_row)
+            .filter(|&&w| w & mask != 0)
+            .count()
+    }
+
+    /// Number of columns in which rows `a` and `b` differ.
+    pub fn row_diff(&self, a: usize, b: usize) -> usize {
+        self.row_words(a)
+            .iter()
+            .zip(self.row_words(b))
+            .map(|(x, y)| (x ^ y).count_ones() as usize)
+            .sum()
+    }
+
+    /// Number of rows in which columns `a` and `b` differ.
+    /// For many column comparisons, `transpose` and `row_diff` are faster.
+    pub fn col_diff(&self, a: usize, b: usize) -> usize {
+        (0..self.rows).filter(|&r| self.get(r, a) != self.get(r, b)).count()
+    }
+
+    pub fn transpose(&self) -> BitGrid {
+        let mut transposed = BitGrid::new(self.cols, self.rows);
+        for (r, c) in self.iter_ones() {
+            transposed.set(c, r, true);
+        }
+        transposed
+    }
+
+    /// Coordinates `(row, col)` of all set cells in row-major order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.rows).flat_map(move |r| self.row(r).iter_ones().map(move |c| (r, c)))
+    }
+
+    pub fn fill(&mut self, value: bool) {
+        for r in 0..self.rows {
+            self.row_mut(r).fill(value);
+        }
+    }
+
+    pub fn fill_row(&mut self, r: usize, value: bool) {
+        self.row_mut(r).fill(value);
+    }
+
+    pub fn fill_col(&mut self, c: usize, value: bool) {
+        for r in 0..self.rows {
+            self.set(r, c, value);
+        }
+    }
+
+    /// Flips every cell, leaving the padding bits untouched.
+    pub fn invert(&mut self) {
+        for r in 0..self.rows {
+            let row = self.row_mut(r);
+            let inverted = !row.to_bitvec();
+            row.copy_from_bitslice(&inverted);
+        }
+    }
+
+    /// Moves every row `by` columns towards column 0, clearing the cells on the right.
+    pub fn shift_left(&mut self, by: usize) {
+        let (by, cols) = (by.min(self.cols), self.cols);
+        for r in 0..self.rows {
+            let row = self.row_mut(r);
+            if by < cols {
+                row.copy_within(by.., 0);
+            }
+            row[cols - by..].fill(false);
+        }
+    }
+
+    /// Moves every row `by` columns away from column 0, clearing the cells on the left.
+    pub fn shift_right(&mut self, by: usize) {
+        let (by, cols) = (by.min(self.cols), self.cols);
+        for r in 0..self.rows {
+            let row = self.row_mut(r);
+            if by < cols {
+                row.copy_within(..cols - by, by);
+            }
+            row[..by].fill(false);
+        }
+    }
+
+    /// Moves every row `by` rows towards row 0, clearing the rows at the bottom.
+    pub fn shift_up(&mut self, by: usize) {
+        let by = by.min(self.rows);
+        let words = self.bits.as_raw_mut_slice();
+        let shift = by * self.words_per_row;
+        words.copy_within(shift.., 0);
+        let len = words.len();
+        words[len - shift..].fill(0);
+    }
+
+    /// Moves every row `by` rows away from row 0, clearing the rows at the top.
+    pub fn shift_down(&mut self, by: usize) {
+        let by = by.min(self.rows);
+        let words = self.bits.as_raw_mut_slice();
+        let shift = by * self.words_per_row;
+        let len = words.len();
+        words.copy_within(..len - shift, shift);
+        words[..shift].fill(0);
+    }
+
+    fn assert_same_shape(&self, other: &BitGrid) {
+        assert!(
+            self.rows == other.rows && self.cols == other.cols,
+            "grids differ in shape"
+        );
+    }
+}
+
+/// Hashes whole words rather than single bits, which is sound because the
+/// padding bits are always zero.
+impl Hash for BitGrid {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rows.hash(state);
+        self.cols.hash(state);
+        self.bits.as_raw_slice().hash(state);
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, other: &BitGrid) {
+        self.assert_same_shape(other);
+        let words = self.bits.as_raw_mut_slice().iter_mut();
+        for (w, o) in words.zip(other.bits.as_raw_slice()) {
+            *w &= o;
+        }
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, other: &BitGrid) {
+        self.assert_same_shape(other);
+        let words = self.bits.as_raw_mut_slice().iter_mut();
+        for (w, o) in words.zip(other.bits.as_raw_slice()) {
+            *w |= o;
+        }
+    }
+}
+
+impl BitXorAssign<&BitGrid> for BitGrid {
+    fn bitxor_assign(&mut self, other: &BitGrid) {
+        self.assert_same_shape(other);
+        let words = self.bits.as_raw_mut_slice().iter_mut();
+        for (w, o) in words.zip(other.bits.as_raw_slice()) {
+            *w ^= o;
+        }
+    }
+}
+
+impl fmt::Display for BitGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for r in 0..self.rows {
+            for bit in self.row(r).iter() {
+                write!(f, "{}", if *bit { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERN: &str = "#.##..##.\n..#.##.#.\n##......#\n##......#\n..#.##.#.\n..##..##.\n#.#.##.#.";
+
+    #[test]
+    fn test_from_str_roundtrip() {
+        let grid = BitGrid::parse(PATTERN, '#');
+        assert_eq!(grid.rows(), 7);
+        assert_eq!(grid.cols(), 9);
+        assert_eq!(grid.to_string().trim_end(), PATTERN);
+    }
+
+    #[test]
+    fn test_popcount() {
+        let grid = BitGrid::parse(PATTERN, '#');
+        assert_eq!(grid.row_count_ones(0), 5);
+        assert_eq!(grid.row_count_ones(2), 3);
+        assert_eq!(grid.col_count_ones(0), 4);
+        assert_eq!(grid.col_count_ones(1), 2);
+        assert_eq!(grid.count_ones(), PATTERN.matches('#').count());
+    }
+
+    #[test]
+    fn test_row_and_col_diff() {
+        let grid = BitGrid::parse(PATTERN, '#');
+        assert_eq!(grid.row_diff(2, 3), 0);
+        assert_eq!(grid.row_diff(1, 4), 0);
+        assert_eq!(grid.row_diff(0, 5), 1);
+        assert_eq!(grid.col_diff(4, 5), 0);
+        assert_eq!(grid.col_diff(0, 1), 2);
+    }
+
+    #[test]
+    fn test_wide_rows() {
+        // rows wider than one word
+        let mut grid = BitGrid::new(2, 130);
+        grid.set(0, 129, true);
+        grid.set(1, 0, true);
+        assert_eq!(grid.row_words(0).len(), 3);
+        assert_eq!(grid.row_diff(0, 1), 2);
+        grid.shift_right(1);
+        assert_eq!(grid.row_count_ones(0), 0);
+        assert!(grid.get(1, 1));
+    }
+
+    #[test]
+    fn test_transpose() {
+        let grid = BitGrid::parse(PATTERN, '#');
+        let transposed = grid.transpose();
+        assert_eq!(transposed.rows(), 9);
+        assert_eq!(transposed.row_diff(4, 5), 0);
+        assert_eq!(transposed.transpose(), grid);
+    }
+
+    #[test]
+    fn test_shift_cols() {
+        let mut grid = BitGrid::parse("#..#\n.##.", '#');
+        grid.shift_left(1);
+        assert_eq!(grid.to_string(), "..#.\n##..\n");
+        grid.shift_right(2);
+        assert_eq!(grid.to_string(), "....\n..##\n");
+        grid.shift_right(10);
+        assert_eq!(grid.count_ones(), 0);
+    }
+
+    #[test]
+    fn test_shift_rows() {
+        let mut grid = BitGrid::parse("#.\n.#\n##", '#');
+        grid.shift_up(1);
+        assert_eq!(grid.to_string(), ".#\n##\n..\n");
+        grid.shift_down(2);
+        assert_eq!(grid.to_string(), "..\n..\n.#\n");
+    }
+
+    #[test]
+    fn test_bulk_operations() {
+        let a = BitGrid::parse("##..\n#.#.", '#');
+        let b = BitGrid::parse("#.#.\n#..#", '#');
+
+        let mut and = a.clone();
+        and &= &b;
+        assert_eq!(and.to_string(), "#...\n#...\n");
+
+        let mut or = a.clone();
+        or |= &b;
+        assert_eq!(or.to_string(), "###.\n#.##\n");
+
+        let mut xor = a.clone();
+        xor ^= &b;
+        assert_eq!(xor.to_string(), ".##.\n..##\n");
+    }
+
+    #[test]
+    fn test_fill_and_invert() {
+        let mut grid = BitGrid::new(3, 5);
+        grid.fill_row(1, true);
+        grid.fill_col(0, true);
+        assert_eq!(grid.count_ones(), 7);
+        grid.invert();
+        assert_eq!(grid.count_ones(), 8);
+        // padding stays clear, so whole-word popcounts still agree
+        assert_eq!(grid.row_count_ones(0), 4);
+        grid.fill(true);
+        assert_eq!(grid.count_ones(), 15);
+    }
+
+    #[test]
+    fn test_col_count_ones_wide() {
+        let mut grid = BitGrid::new(3, 130);
+        grid.fill_col(129, true);
+        grid.set(1, 64, true);
+        assert_eq!(grid.col_count_ones(129), 3);
+        assert_eq!(grid.col_count_ones(64), 1);
+        assert_eq!(grid.col_count_ones(0), 0);
+        assert_eq!(BitGrid::new(0, 5).col_count_ones(4), 0);
+        assert_eq!(BitGrid::new(0, 130).col_count_ones(100), 0);
+    }
+
+    #[test]
+    fn test_hash_follows_equality() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |grid: &BitGrid| {
+            let mut hasher = DefaultHasher::new();
+            grid.hash(&mut hasher);
+            hasher.finish()
+        };
+        let a = BitGrid::parse(PATTERN, '#');
+        let mut b = a.clone();
+        b.invert();
+        b.invert();
+        assert_eq!(hash(&a), hash(&b));
+        b.set(3, 3, true);
+        assert_ne!(hash(&a), hash(&b));
+        // same words, different shape
+        assert_ne!(hash(&BitGrid::new(2, 3)), hash(&BitGrid::new(2, 4)));
+    }
+
+    #[test]
+    #[should_panic(expected = "grids differ in shape")]
+    fn test_bulk_operation_shape_mismatch() {
+        let mut a = BitGrid::new(2, 2);
+        a |= &BitGrid::new(2, 3);
+    }
+}
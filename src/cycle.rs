@@ -0,0 +1,219 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cycle in the sequence `x0, f(x0), f(f(x0)), ...`.
+/// The state after `start` steps is the first one that repeats,
+/// and it repeats every `length` steps from then on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Maps step `n` to the smallest step that yields the same state.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            return n;
+        }
+        self.start + (n - self.start) % self.length
+    }
+}
+
+/// Runs `step` on `state` until a key repeats or `limit` steps are done.
+/// On a repeat, `state` is left at step `start + length` (equal to step `start`).
+fn detect<T, K>(
+    state: &mut T,
+    step: &mut impl FnMut(&mut T),
+    key: &mut impl FnMut(&T) -> K,
+    limit: usize,
+) -> Option<Cycle>
+where
+    K: Hash + Eq,
+{
+    let mut seen = HashMap::new();
+    for i in 0..=limit {
+        match seen.entry(key(state)) {
+            Entry::Occupied(e) => {
+                let start = *e.get();
+                return Some(Cycle { start, length: i - start });
+            }
+            Entry::Vacant(e) => {
+                e.insert(i);
+            }
+        }
+        if i < limit {
+            step(state);
+        }
+    }
+    None
+}
+
+/// Finds the cycle by hashing every visited state.
+/// Never returns if the sequence does not cycle.
+pub fn find<T>(initial: &T, step: impl FnMut(&mut T)) -> Cycle
+where
+    T: Hash + Eq + Clone,
+{
+    find_by_key(initial, step, T::clone)
+}
+
+/// Like `find`, but only stores `key(state)` instead of the whole state.
+/// The key has to identify the state uniquely, e.g. a compact encoding of a grid.
+pub fn find_by_key<T, K>(
+    initial: &T,
+    mut step: impl FnMut(&mut T),
+    mut key: impl FnMut(&T) -> K,
+) -> Cycle
+where
+    T: Clone,
+    K: Hash + Eq,
+{
+    let mut state = initial.clone();
+    detect(&mut state, &mut step, &mut key, usize::MAX).expect("sequence does not cycle")
+}
+
+/// Finds the cycle with Brent's algorithm.
+/// Needs no extra memory besides two states, but calls `step` more often than `find`.
+pub fn brent<T>(initial: &T, mut step: impl FnMut(&mut T)) -> Cycle
+where
+    T: Eq + Clone,
+{
+    // find the cycle length by moving the tortoise to the hare at every power of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    step(&mut hare);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        step(&mut hare);
+        length += 1;
+    }
+
+    // start both one cycle length apart and walk until they meet at the start
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        step(&mut hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Returns the state after `n` steps without running all of them.
+pub fn nth<T>(initial: T, step: impl FnMut(&mut T), n: usize) -> T
+where
+    T: Hash + Eq + Clone,
+{
+    nth_by_key(initial, step, T::clone, n)
+}
+
+/// Like `nth`, but only stores `key(state)` for every visited state.
+pub fn nth_by_key<T, K>(
+    mut state: T,
+    mut step: impl FnMut(&mut T),
+    mut key: impl FnMut(&T) -> K,
+    n: usize,
+) -> T
+where
+    K: Hash + Eq,
+{
+    if let Some(cycle) = detect(&mut state, &mut step, &mut key, n) {
+        // state is at step start + length, which is the same as step start
+        let remaining = cycle.reduce(n) - cycle.start;
+        for _ in 0..remaining {
+            step(&mut state);
+        }
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use grid::Grid;
+
+    use super::*;
+
+    // 0, 1, 2, 5, 26, 17, 15, 6, 37, 50, 26, ... -> cycle of length 6 starting at 4
+    fn square_plus_one(x: &mut u32) {
+        *x = (*x * *x + 1) % 55;
+    }
+
+    fn naive_nth(mut x: u32, n: usize) -> u32 {
+        for _ in 0..n {
+            square_plus_one(&mut x);
+        }
+        x
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(&0, square_plus_one), Cycle { start: 4, length: 6 });
+    }
+
+    #[test]
+    fn test_brent_matches_find() {
+        for x in 0..55 {
+            assert_eq!(brent(&x, square_plus_one), find(&x, square_plus_one));
+        }
+    }
+
+    #[test]
+    fn test_pure_cycle() {
+        let rotate = |x: &mut u32| *x = (*x + 1) % 5;
+        assert_eq!(find(&2, rotate), Cycle { start: 0, length: 5 });
+        assert_eq!(brent(&2, rotate), Cycle { start: 0, length: 5 });
+    }
+
+    #[test]
+    fn test_reduce() {
+        let cycle = Cycle { start: 4, length: 6 };
+        assert_eq!(cycle.reduce(2), 2);
+        assert_eq!(cycle.reduce(4), 4);
+        assert_eq!(cycle.reduce(7), 7);
+        assert_eq!(cycle.reduce(10), 4);
+        assert_eq!(cycle.reduce(1_000_000_000), 4);
+    }
+
+    #[test]
+    fn test_nth() {
+        for n in 0..50 {
+            assert_eq!(nth(0, square_plus_one, n), naive_nth(0, n));
+        }
+        assert_eq!(nth(0, square_plus_one, 1_000_000_000), naive_nth(0, 4));
+    }
+
+    #[test]
+    fn test_nth_by_key() {
+        let state = (0u32, "payload that is not part of the key");
+        let step = |s: &mut (u32, &str)| square_plus_one(&mut s.0);
+        assert_eq!(nth_by_key(state, step, |s| s.0, 1_000_000_000).0, naive_nth(0, 4));
+    }
+
+    #[test]
+    fn test_grid_state() {
+        // rotating the rows of a 3x2 grid repeats after three steps
+        let grid = Grid::from_vec(vec![1u8, 2, 3, 4, 5, 6], 2);
+        let rotate_rows = |g: &mut Grid<u8>| {
+            let first = g.iter_row(0).cloned().collect::<Vec<_>>();
+            g.remove_row(0);
+            g.push_row(first);
+        };
+        assert_eq!(find(&grid, rotate_rows), Cycle { start: 0, length: 3 });
+        assert_eq!(brent(&grid, rotate_rows), Cycle { start: 0, length: 3 });
+        let after = nth(grid, rotate_rows, 1_000_000_001);
+        assert_eq!(after.iter_row(0).cloned().collect::<Vec<_>>(), vec![5, 6]);
+    }
+}
@@ -1,6 +1,11 @@
 use std::env;
 use std::fs;
 
+pub mod bitgrid;
+pub mod cycle;
+pub mod memo;
+pub mod polygon;
+pub mod rng;
 pub mod solutions;
 
 pub fn read_file(folder: &str, day: u8) -> String {
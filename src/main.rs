@@ -37,11 +37,35 @@ macro_rules! solve_day {
     }};
 }
 
+fn explain_day(day: u8, input: &str, json: bool) {
+    match day {
+        7 => {
+            println!("🎄 {}Part 1 ranking{} 🎄", ANSI_BOLD, ANSI_RESET);
+            print!("{}", day07::explain(input, day07::Ruleset::Standard));
+            println!("🎄 {}Part 2 ranking{} 🎄", ANSI_BOLD, ANSI_RESET);
+            print!("{}", day07::explain(input, day07::Ruleset::JokersWild));
+        },
+        15 => print!("{}", day15::explain(input, json)),
+        _ => println!("no explanation for day: {}", day),
+    }
+}
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let day: u8 = args[1].clone().parse().unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let explain = args.iter().any(|arg| arg == "--explain");
+    let json = args.iter().any(|arg| arg == "--json");
+    let day: u8 = args.iter()
+        .find(|arg| !arg.starts_with("--"))
+        .expect("usage: aoc <day> [--explain [--json]]")
+        .parse()
+        .unwrap();
     let input = read_file("inputs", day);
 
+    if explain {
+        explain_day(day, &input, json);
+        return;
+    }
+
     match day {
         1 => solve_day!(day01, &input),
         2 => solve_day!(day02, &input),
@@ -50,8 +74,13 @@ fn main() {
         5 => solve_day!(day05, &input),
         6 => solve_day!(day06, &input),
         7 => solve_day!(day07, &input),
+        8 => solve_day!(day08, &input),
         9 => solve_day!(day09, &input),
+        10 => solve_day!(day10, &input),
         11 => solve_day!(day11, &input),
+        12 => solve_day!(day12, &input),
+        13 => solve_day!(day13, &input),
+        14 => solve_day!(day14, &input),
         15 => solve_day!(day15, &input),
         _ => println!("day not solved: {}", day),
     }
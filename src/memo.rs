@@ -0,0 +1,268 @@
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+/// Storage for memoised values.
+pub trait Store<K, V> {
+    fn get(&self, key: &K) -> Option<&V>;
+    fn insert(&mut self, key: K, value: V);
+    fn clear(&mut self);
+}
+
+impl<K: Hash + Eq, V> Store<K, V> for FxHashMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        FxHashMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        FxHashMap::insert(self, key, value);
+    }
+
+    fn clear(&mut self) {
+        FxHashMap::clear(self);
+    }
+}
+
+/// A key that can be laid out in a dense array,
+/// given the exclusive upper bound of every component.
+pub trait DenseKey {
+    type Dims: Copy;
+
+    fn size(dims: Self::Dims) -> usize;
+    fn index(&self, dims: Self::Dims) -> usize;
+}
+
+impl DenseKey for usize {
+    type Dims = usize;
+
+    fn size(dims: usize) -> usize {
+        dims
+    }
+
+    fn index(&self, dims: usize) -> usize {
+        assert!(*self < dims, "key out of range");
+        *self
+    }
+}
+
+impl DenseKey for (usize, usize) {
+    type Dims = (usize, usize);
+
+    fn size((a, b): (usize, usize)) -> usize {
+        a * b
+    }
+
+    fn index(&self, (a, b): (usize, usize)) -> usize {
+        assert!(self.0 < a && self.1 < b, "key out of range");
+        self.0 * b + self.1
+    }
+}
+
+impl DenseKey for (usize, usize, usize) {
+    type Dims = (usize, usize, usize);
+
+    fn size((a, b, c): (usize, usize, usize)) -> usize {
+        a * b * c
+    }
+
+    fn index(&self, (a, b, c): (usize, usize, usize)) -> usize {
+        assert!(self.0 < a && self.1 < b && self.2 < c, "key out of range");
+        (self.0 * b + self.1) * c + self.2
+    }
+}
+
+/// Array-backed storage for keys from a small product of ranges.
+pub struct Dense<K: DenseKey, V> {
+    dims: K::Dims,
+    values: Vec<Option<V>>,
+}
+
+impl<K: DenseKey, V> Dense<K, V> {
+    pub fn new(dims: K::Dims) -> Self {
+        let values = std::iter::repeat_with(|| None).take(K::size(dims)).collect();
+        Dense { dims, values }
+    }
+}
+
+impl<K: DenseKey, V> Store<K, V> for Dense<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        self.values[key.index(self.dims)].as_ref()
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.values[key.index(self.dims)] = Some(value);
+    }
+
+    fn clear(&mut self) {
+        self.values.iter_mut().for_each(|v| *v = None);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Stats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / total as f64
+    }
+}
+
+/// Cache for a memoised recursive function.
+///
+/// The function gets a callback for its recursive calls, so it can be a closure:
+/// ```
+/// use aoc::memo::Memo;
+///
+/// let mut memo = Memo::new();
+/// let fib = memo.solve(80u64, &|fib, n| if n < 2 { n } else { fib(n - 1) + fib(n - 2) });
+/// assert_eq!(fib, 23416728348467685);
+/// ```
+pub struct Memo<K, V, S = FxHashMap<K, V>> {
+    store: S,
+    stats: Stats,
+    _marker: std::marker::PhantomData<(K, V)>,
+}
+
+impl<K: Hash + Eq, V> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo::with_store(FxHashMap::default())
+    }
+}
+
+impl<K: Hash + Eq, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo::new()
+    }
+}
+
+impl<K: DenseKey, V> Memo<K, V, Dense<K, V>> {
+    pub fn dense(dims: K::Dims) -> Self {
+        Memo::with_store(Dense::new(dims))
+    }
+}
+
+impl<K, V, S: Store<K, V>> Memo<K, V, S> {
+    pub fn with_store(store: S) -> Self {
+        Memo {
+            store,
+            stats: Stats::default(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Drops all cached values and resets the statistics.
+    pub fn clear(&mut self) {
+        self.store.clear();
+        self.stats = Stats::default();
+    }
+}
+
+impl<K, V, S> Memo<K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Store<K, V>,
+{
+    /// Evaluates `f(key)`, where `f` calls its first argument to recurse.
+    /// Results are kept across calls, so `f` has to be the same function every time.
+    pub fn solve<F>(&mut self, key: K, f: &F) -> V
+    where
+        F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+    {
+        if let Some(value) = self.store.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = f(&mut |k| self.solve(k, f), key.clone());
+        self.store.insert(key, value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts arrangements of a spring row as in the day 12 example, keyed by
+    // (position in the row, index of the next group).
+    fn arrangements(springs: &str, groups: &[usize]) -> u64 {
+        let springs = springs.as_bytes();
+        let count = |rec: &mut dyn FnMut((usize, usize)) -> u64, (pos, group): (usize, usize)| {
+            if pos >= springs.len() {
+                return (group == groups.len()) as u64;
+            }
+            let mut total = 0;
+            if springs[pos] != b'#' {
+                total += rec((pos + 1, group));
+            }
+            if springs[pos] != b'.' && group < groups.len() {
+                let end = pos + groups[group];
+                let fits = end <= springs.len()
+                    && springs[pos..end].iter().all(|&c| c != b'.')
+                    && springs.get(end) != Some(&b'#');
+                if fits {
+                    total += rec(((end + 1).min(springs.len()), group + 1));
+                }
+            }
+            total
+        };
+
+        let mut hashed = Memo::new();
+        let mut dense = Memo::dense((springs.len() + 1, groups.len() + 1));
+        let result = hashed.solve((0, 0), &count);
+        assert_eq!(dense.solve((0, 0), &count), result);
+        assert_eq!(hashed.stats(), dense.stats());
+        result
+    }
+
+    #[test]
+    fn test_spring_example() {
+        assert_eq!(arrangements("???.###", &[1, 1, 3]), 1);
+        assert_eq!(arrangements(".??..??...?##.", &[1, 1, 3]), 4);
+        assert_eq!(arrangements("?###????????", &[3, 2, 1]), 10);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut memo = Memo::new();
+        let fib = |fib: &mut dyn FnMut(u64) -> u64, n: u64| if n < 2 { n } else { fib(n - 1) + fib(n - 2) };
+        assert_eq!(memo.solve(10, &fib), 55);
+        // every n in 0..=10 is computed once, every n in 1..=8 is also looked up once
+        assert_eq!(memo.stats(), Stats { hits: 8, misses: 11 });
+
+        assert_eq!(memo.solve(10, &fib), 55);
+        assert_eq!(memo.stats(), Stats { hits: 9, misses: 11 });
+        assert!((memo.stats().hit_rate() - 0.45).abs() < 1e-9);
+
+        memo.clear();
+        assert_eq!(memo.stats(), Stats::default());
+        assert_eq!(memo.stats().hit_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_dense_keys() {
+        assert_eq!(7.index(10), 7);
+        assert_eq!((2, 3).index((4, 5)), 13);
+        assert_eq!((1, 2, 3).index((2, 3, 4)), 23);
+        assert_eq!(<(usize, usize, usize)>::size((2, 3, 4)), 24);
+    }
+
+    #[test]
+    #[should_panic(expected = "key out of range")]
+    fn test_dense_key_out_of_range() {
+        let mut memo = Memo::dense(3);
+        memo.solve(5, &|_, n: usize| n);
+    }
+}
@@ -0,0 +1,186 @@
+use num::integer::gcd;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'U' | 'N' => Some(Direction::Up),
+            'D' | 'S' => Some(Direction::Down),
+            'L' | 'W' => Some(Direction::Left),
+            'R' | 'E' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// Unit step as `(x, y)`, with y growing downwards.
+    pub fn delta(&self) -> (i64, i64) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+/// A closed polygon on the integer lattice.
+/// The last vertex is implicitly connected back to the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<(i64, i64)>,
+}
+
+impl Polygon {
+    pub fn from_points(points: impl IntoIterator<Item = (i64, i64)>) -> Self {
+        Polygon { vertices: points.into_iter().collect() }
+    }
+
+    /// Walks the moves starting at the origin, e.g. a day 18 dig plan.
+    pub fn from_moves(moves: impl IntoIterator<Item = (Direction, i64)>) -> Self {
+        let mut vertices = vec![(0, 0)];
+        let (mut x, mut y) = (0i64, 0i64);
+        for (direction, length) in moves {
+            let (dx, dy) = direction.delta();
+            x += dx * length;
+            y += dy * length;
+            vertices.push((x, y));
+        }
+        // a closed walk ends where it started, which is already the first vertex
+        if vertices.len() > 1 && vertices.last() == vertices.first() {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[(i64, i64)] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = ((i64, i64), (i64, i64))> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next.copied())
+    }
+
+    /// Twice the enclosed area (shoelace formula), which is always an integer.
+    pub fn double_area(&self) -> i128 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| x1 as i128 * y2 as i128 - x2 as i128 * y1 as i128)
+            .sum::<i128>()
+            .abs()
+    }
+
+    /// Enclosed area, rounded down if it is not a whole number.
+    pub fn area(&self) -> i128 {
+        self.double_area() / 2
+    }
+
+    /// Number of lattice points on the edges.
+    pub fn boundary_points(&self) -> i128 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| gcd((x2 - x1) as i128, (y2 - y1) as i128))
+            .sum()
+    }
+
+    /// Number of lattice points strictly inside, by Pick's theorem `A = I + B/2 - 1`.
+    pub fn interior_points(&self) -> i128 {
+        if self.vertices.len() < 3 {
+            return 0;
+        }
+        (self.double_area() - self.boundary_points() + 2) / 2
+    }
+
+    /// Number of lattice points inside or on the boundary.
+    pub fn lattice_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::read_file;
+
+    use super::*;
+
+    fn parse_dig_plan(input: &str, hex: bool) -> Polygon {
+        let moves = input.lines().map(|line| {
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+            if hex {
+                let code = parts[2].trim_matches(|c| c == '(' || c == ')' || c == '#');
+                let length = i64::from_str_radix(&code[..5], 16).unwrap();
+                let direction = match &code[5..] {
+                    "0" => Direction::Right,
+                    "1" => Direction::Down,
+                    "2" => Direction::Left,
+                    _ => Direction::Up,
+                };
+                (direction, length)
+            } else {
+                let direction = Direction::from_char(parts[0].chars().next().unwrap()).unwrap();
+                (direction, parts[1].parse().unwrap())
+            }
+        });
+        Polygon::from_moves(moves)
+    }
+
+    #[test]
+    fn test_dig_plan() {
+        let input = read_file("examples", 18);
+        let polygon = parse_dig_plan(&input, false);
+        assert_eq!(polygon.vertices().len(), 14);
+        assert_eq!(polygon.boundary_points(), 38);
+        assert_eq!(polygon.lattice_points(), 62);
+    }
+
+    #[test]
+    fn test_dig_plan_hex() {
+        let input = read_file("examples", 18);
+        let polygon = parse_dig_plan(&input, true);
+        assert_eq!(polygon.lattice_points(), 952408144115);
+    }
+
+    #[test]
+    fn test_triangle() {
+        let polygon = Polygon::from_points([(0, 0), (4, 0), (0, 4)]);
+        assert_eq!(polygon.double_area(), 16);
+        assert_eq!(polygon.area(), 8);
+        assert_eq!(polygon.boundary_points(), 12);
+        assert_eq!(polygon.interior_points(), 3);
+    }
+
+    #[test]
+    fn test_orientation_does_not_matter() {
+        let clockwise = Polygon::from_points([(0, 0), (3, 0), (3, 2), (0, 2)]);
+        let counter_clockwise = Polygon::from_points([(0, 0), (0, 2), (3, 2), (3, 0)]);
+        assert_eq!(clockwise.double_area(), 12);
+        assert_eq!(counter_clockwise.double_area(), 12);
+        assert_eq!(clockwise.interior_points(), 2);
+    }
+
+    #[test]
+    fn test_large_coordinates() {
+        let side = 3_000_000_000i64;
+        let moves = [
+            (Direction::Right, side),
+            (Direction::Down, side),
+            (Direction::Left, side),
+            (Direction::Up, side),
+        ];
+        let polygon = Polygon::from_moves(moves);
+        assert_eq!(polygon.vertices().len(), 4);
+        assert_eq!(polygon.area(), side as i128 * side as i128);
+        assert_eq!(polygon.lattice_points(), (side as i128 + 1).pow(2));
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert_eq!(Polygon::from_points([]).lattice_points(), 0);
+        assert_eq!(Polygon::from_points([(1, 1)]).interior_points(), 0);
+    }
+}
@@ -0,0 +1,48 @@
+/// A small seeded pseudo-random generator (64-bit LCG) for generated test and bench inputs.
+/// Not for anything that needs good randomness, but the same seed always gives the same input.
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg { state: seed }
+    }
+
+    /// The next 31 random bits, the low bits of an LCG are not very random
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.state >> 33) as u32
+    }
+
+    /// A number in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        self.next_u32() as usize % n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_numbers() {
+        let (mut a, mut b) = (Lcg::new(42), Lcg::new(42));
+        let xs = (0..100).map(|_| a.below(1000)).collect::<Vec<_>>();
+        assert_eq!(xs, (0..100).map(|_| b.below(1000)).collect::<Vec<_>>());
+        assert!(xs.iter().all(|&x| x < 1000));
+        assert_ne!(Lcg::new(43).next_u32(), Lcg::new(42).next_u32());
+    }
+
+    #[test]
+    fn test_below_covers_range() {
+        let mut rng = Lcg::new(7);
+        let mut seen = [false; 6];
+        for _ in 0..200 {
+            seen[rng.below(6)] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+}
@@ -1,59 +1,84 @@
-fn find_digits(line: &str) -> Vec<u32> {
-    return line
-        .chars()
-        .filter(|c| c.is_digit(10))
-        .map(|c| c.to_digit(10).unwrap())
-        .collect();
-}
+use std::error::Error;
+
+/// The spelled-out digits of part two
+pub const ENGLISH: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
 
-fn combine_first_and_last(digits: Vec<u32>) -> u32 {
-    let first = digits[0].to_string();
-    let last = digits[digits.len() - 1].to_string();
-    format!("{}{}", first, last).parse::<u32>().unwrap()
+/// Finds digits in a line, either as ASCII digits or as one of the words in its table.
+/// Words may overlap, `eightwo` holds an 8 followed by a 2.
+pub struct Scanner<'a> {
+    words: &'a [(&'a str, u32)],
 }
 
-fn replace(line: &str) -> String {
-    let replacements = [
-        ("one", "o1ne"),
-        ("two", "t2wo"),
-        ("three", "t3hree"),
-        ("four", "f4our"),
-        ("five", "f5ive"),
-        ("six", "s6ix"),
-        ("seven", "s7even"),
-        ("eight", "e8ight"),
-        ("nine", "n9ne"),
-    ];
+impl<'a> Scanner<'a> {
+    pub fn new(words: &'a [(&'a str, u32)]) -> Self {
+        Scanner { words }
+    }
+
+    /// Only recognises ASCII digits
+    pub fn digits_only() -> Self {
+        Scanner { words: &[] }
+    }
+
+    /// The digit starting at byte `i`, if there is one
+    fn digit_at(&self, bytes: &[u8], i: usize) -> Option<u32> {
+        if bytes[i].is_ascii_digit() {
+            return Some((bytes[i] - b'0') as u32);
+        }
+        self.words.iter()
+            .find(|(word, _)| bytes[i..].starts_with(word.as_bytes()))
+            .map(|&(_, value)| value)
+    }
+
+    pub fn first(&self, line: &str) -> Option<u32> {
+        let bytes = line.as_bytes();
+        (0..bytes.len()).find_map(|i| self.digit_at(bytes, i))
+    }
 
-    let mut result = line.to_string();
+    pub fn last(&self, line: &str) -> Option<u32> {
+        let bytes = line.as_bytes();
+        (0..bytes.len()).rev().find_map(|i| self.digit_at(bytes, i))
+    }
 
-    for &(original, replacement) in &replacements {
-        result = result.replace(original, replacement);
+    /// The first and the last digit of the line read as a two-digit number
+    pub fn calibration_value(&self, line: &str) -> Result<u32, Box<dyn Error>> {
+        match (self.first(line), self.last(line)) {
+            (Some(first), Some(last)) => Ok(first * 10 + last),
+            _ => Err(format!("no digits in line {:?}", line).into()),
+        }
     }
 
-    result
+    pub fn sum(&self, input: &str) -> Result<u32, Box<dyn Error>> {
+        input.lines().map(|line| self.calibration_value(line)).sum()
+    }
 }
 
 pub fn part_one(input: &str) -> u32 {
-    input
-        .lines()
-        .map(find_digits)
-        .map(combine_first_and_last)
-        .sum()
+    match Scanner::digits_only().sum(input) {
+        Ok(sum) => sum,
+        Err(e) => panic!("{}", e),
+    }
 }
 
 pub fn part_two(input: &str) -> u32 {
-    input
-        .lines()
-        .map(replace)
-        .map(|line| find_digits(&line))
-        .map(combine_first_and_last)
-        .sum()
+    match Scanner::new(&ENGLISH).sum(input) {
+        Ok(sum) => sum,
+        Err(e) => panic!("{}", e),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::read_file;
+    use crate::{read_file, read_file_with_name};
 
     use super::*;
 
@@ -69,4 +94,43 @@ mod tests {
         let input = read_file("inputs", 1);
         assert_eq!(part_two(&input), 54770);
     }
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(part_one(&read_file_with_name("examples", "01_1")), 142);
+        assert_eq!(part_two(&read_file_with_name("examples", "01_2")), 281);
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let scanner = Scanner::new(&ENGLISH);
+        assert_eq!(scanner.calibration_value("eightwo").unwrap(), 82);
+        assert_eq!(scanner.calibration_value("twone").unwrap(), 21);
+        assert_eq!(scanner.calibration_value("oneight3sevenine").unwrap(), 19);
+        assert_eq!(scanner.calibration_value("five").unwrap(), 55);
+        // words do not count without a table
+        assert_eq!(Scanner::digits_only().calibration_value("one2three").unwrap(), 22);
+    }
+
+    #[test]
+    fn test_other_word_tables() {
+        let german = [("eins", 1), ("zwei", 2), ("drei", 3), ("vier", 4), ("fünf", 5)];
+        let scanner = Scanner::new(&german);
+        assert_eq!(scanner.calibration_value("xzweiabcfünfy").unwrap(), 25);
+        assert_eq!(scanner.calibration_value("dreins").unwrap(), 31);
+        assert!(scanner.calibration_value("one two").is_err());
+    }
+
+    #[test]
+    fn test_line_without_digits() {
+        let error = Scanner::new(&ENGLISH).sum("1abc2\nnothing here").expect_err("should fail");
+        assert_eq!(error.to_string(), "no digits in line \"nothing here\"");
+        assert!(Scanner::digits_only().calibration_value("").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "no digits in line")]
+    fn test_part_one_without_digits() {
+        part_one("abc");
+    }
 }
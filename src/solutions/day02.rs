@@ -1,70 +1,118 @@
-use regex::Regex;
+use std::collections::BTreeMap;
+use std::error::Error;
 
-const GREEN_REGEX: &str = r"(\d+) green";
-const RED_REGEX: &str = r"(\d+) red";
-const BLUE_REGEX: &str = r"(\d+) blue";
+const COLOURS: [&str; 3] = ["red", "green", "blue"];
 
-fn get(line: &str, re: &Regex) -> Vec<u32> {
-    re.captures_iter(line)
-        .filter_map(|cap| cap[1].parse().ok())
-        .collect()
+/// Cubes per colour, a colour that is not listed counts as zero
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bag {
+    cubes: BTreeMap<String, u32>
 }
 
-fn extract_game_number(line: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let re = Regex::new(r"Game (\d+):")?;
-    let caps = re.captures(line).ok_or("No captures")?;
-    let game_number_str = caps.get(1).ok_or("No game number")?.as_str();
-    let game_number = game_number_str.parse::<u32>()?;
-    Ok(game_number)
+impl Bag {
+    pub fn new(cubes: &[(&str, u32)]) -> Self {
+        Bag {
+            cubes: cubes.iter().map(|&(colour, count)| (colour.to_string(), count)).collect()
+        }
+    }
+
+    pub fn count(&self, colour: &str) -> u32 {
+        self.cubes.get(colour).copied().unwrap_or(0)
+    }
+
+    /// Product of the counts of the given colours
+    pub fn power(&self, colours: &[&str]) -> u32 {
+        colours.iter().map(|colour| self.count(colour)).product()
+    }
+
+    fn raise(&mut self, colour: &str, count: u32) {
+        let current = self.cubes.entry(colour.to_string()).or_insert(0);
+        *current = (*current).max(count);
+    }
 }
 
-fn process_line(line: &str) -> (Option<u32>, Option<u32>, Option<u32>) {
-    let green_regex = Regex::new(GREEN_REGEX).unwrap();
-    let red_regex = Regex::new(RED_REGEX).unwrap();
-    let blue_regex = Regex::new(BLUE_REGEX).unwrap();
+/// The cubes shown in one round, e.g. `3 blue, 4 red`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Draw {
+    pub cubes: Vec<(String, u32)>
+}
 
-    let green = get(line, &green_regex);
-    let red = get(line, &red_regex);
-    let blue = get(line, &blue_regex);
+impl Draw {
+    pub fn parse(s: &str) -> Result<Self, Box<dyn Error>> {
+        let mut cubes = vec![];
+        for cube in s.split(',').map(str::trim).filter(|cube| !cube.is_empty()) {
+            let (count, colour) = cube.split_once(' ')
+                .ok_or_else(|| format!("expected \"<count> <colour>\", got {:?}", cube))?;
+            cubes.push((colour.trim().to_string(), count.parse()?));
+        }
+        Ok(Draw { cubes })
+    }
 
-    let max_green = green.iter().max().cloned();
-    let max_red = red.iter().max().cloned();
-    let max_blue = blue.iter().max().cloned();
+    pub fn count(&self, colour: &str) -> u32 {
+        self.cubes.iter()
+            .filter(|(c, _)| c == colour)
+            .map(|(_, count)| count)
+            .sum()
+    }
 
-    (max_green, max_red, max_blue)
+    pub fn fits_in(&self, bag: &Bag) -> bool {
+        self.cubes.iter().all(|(colour, _)| self.count(colour) <= bag.count(colour))
+    }
 }
 
-pub fn part_one(input: &str) -> u32 {
-    input.lines()
-        .filter(|line| {
-            let (max_green, max_red, max_blue) = process_line(line);
-            match (max_green, max_red, max_blue) {
-                (Some(green), Some(red), Some(blue)) => {
-                    red <= 12 && green <= 13 && blue <= 14
-                },
-                _ => panic!("max value could not be computed for one of the colors")
-            }
-        })
-        .map(|line| {
-            match extract_game_number(line) {
-                Ok(game_number) => game_number,
-                Err(e) => panic!("Could not extract game number: {}", e)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    pub id: u32,
+    pub rounds: Vec<Draw>
+}
+
+impl Game {
+    /// Parses `Game 1: 3 blue, 4 red; 1 red, 2 green`
+    pub fn parse(line: &str) -> Result<Self, Box<dyn Error>> {
+        let (header, rounds) = line.split_once(':').ok_or("missing ':' after the game id")?;
+        let id = header.trim()
+            .strip_prefix("Game ")
+            .ok_or_else(|| format!("expected \"Game <id>\", got {:?}", header))?
+            .trim()
+            .parse()?;
+        let rounds = rounds.split(';').map(Draw::parse).collect::<Result<_, _>>()?;
+        Ok(Game { id, rounds })
+    }
+
+    pub fn is_possible(&self, bag: &Bag) -> bool {
+        self.rounds.iter().all(|draw| draw.fits_in(bag))
+    }
+
+    /// The fewest cubes of every colour that make the game possible
+    pub fn minimum_bag(&self) -> Bag {
+        let mut bag = Bag::default();
+        for draw in &self.rounds {
+            for (colour, _) in &draw.cubes {
+                bag.raise(colour, draw.count(colour));
             }
-        })
+        }
+        bag
+    }
+}
+
+fn parse_games(input: &str) -> impl Iterator<Item = Game> + '_ {
+    input.lines().map(|line| match Game::parse(line) {
+        Ok(game) => game,
+        Err(e) => panic!("could not parse {:?}: {}", line, e)
+    })
+}
+
+pub fn part_one(input: &str) -> u32 {
+    let bag = Bag::new(&[("red", 12), ("green", 13), ("blue", 14)]);
+    parse_games(input)
+        .filter(|game| game.is_possible(&bag))
+        .map(|game| game.id)
         .sum()
 }
 
 pub fn part_two(input: &str) -> u32 {
-    input.lines()
-        .map(|line| {
-            let (max_green, max_red, max_blue) = process_line(line);
-            match (max_green, max_red, max_blue) {
-                (Some(green), Some(red), Some(blue)) => {
-                    red * green * blue
-                },
-                _ => panic!("max value could not be computed for one of the colors")
-            }
-        })
+    parse_games(input)
+        .map(|game| game.minimum_bag().power(&COLOURS))
         .sum()
 }
 
@@ -86,4 +134,53 @@ mod tests {
         let input = read_file("inputs", 2);
         assert_eq!(part_two(&input), 63981);
     }
+
+    #[test]
+    fn test_examples() {
+        let input = read_file("examples", 2);
+        assert_eq!(part_one(&input), 8);
+        assert_eq!(part_two(&input), 2286);
+    }
+
+    #[test]
+    fn test_game_parse() {
+        let game = Game::parse("Game 12: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert_eq!(game.id, 12);
+        assert_eq!(game.rounds.len(), 3);
+        assert_eq!(game.rounds[0], Draw { cubes: vec![("blue".to_string(), 3), ("red".to_string(), 4)] });
+        assert_eq!(game.rounds[2].count("red"), 0);
+
+        assert!(Game::parse("Game x: 1 red").is_err());
+        assert!(Game::parse("Game 1 1 red").is_err());
+        assert!(Game::parse("Game 1: red").is_err());
+        assert!(Game::parse("Game 1: many red").is_err());
+    }
+
+    #[test]
+    fn test_possible_and_minimum_bag() {
+        let game = Game::parse("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert!(game.is_possible(&Bag::new(&[("red", 4), ("green", 2), ("blue", 6)])));
+        assert!(!game.is_possible(&Bag::new(&[("red", 4), ("green", 2), ("blue", 5)])));
+        // a colour missing from the bag means there are none of it
+        assert!(!game.is_possible(&Bag::new(&[("red", 4), ("blue", 6)])));
+
+        let bag = game.minimum_bag();
+        assert_eq!(bag, Bag::new(&[("red", 4), ("green", 2), ("blue", 6)]));
+        assert_eq!(bag.power(&COLOURS), 48);
+    }
+
+    #[test]
+    fn test_missing_and_other_colours() {
+        // no blue at all: the minimum bag has zero blue instead of panicking
+        let game = Game::parse("Game 1: 3 red; 2 green").unwrap();
+        assert_eq!(game.minimum_bag().count("blue"), 0);
+        assert_eq!(part_two("Game 1: 3 red; 2 green"), 0);
+        assert_eq!(part_one("Game 1: 3 red; 2 green"), 1);
+
+        let game = Game::parse("Game 7: 2 purple, 1 teal; 5 purple").unwrap();
+        assert_eq!(game.minimum_bag().power(&["purple", "teal"]), 5);
+        assert!(game.is_possible(&Bag::new(&[("purple", 5), ("teal", 1)])));
+        // colours the standard bag does not know about cannot be drawn from it
+        assert_eq!(part_one("Game 7: 2 purple"), 0);
+    }
 }
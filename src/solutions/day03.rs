@@ -1,111 +1,156 @@
-use regex::Regex;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Empty,
+    /// The symbol with this index
+    Symbol(usize),
+    /// Part of the number with this index
+    Digit(usize),
+}
+
+/// A number spanning the columns `start..end` of its row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Number {
+    pub value: u32,
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+}
 
-struct Coordinate {
-    x: u32,
-    y: u32,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub kind: char,
+    pub row: usize,
+    pub col: usize,
 }
 
-impl Coordinate {
-    fn new(x: u32, y: u32) -> Self {
-        Self { x, y }
+/// The engine schematic: its numbers, its symbols and which of them touch.
+/// A number that touches at least one symbol is a part.
+pub struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    /// For every number, the indices of the symbols around it
+    number_symbols: Vec<Vec<usize>>,
+    /// For every symbol, the indices of the numbers around it
+    symbol_numbers: Vec<Vec<usize>>,
+}
+
+impl Schematic {
+    pub fn parse(input: &str) -> Self {
+        let lines = input.lines().collect::<Vec<_>>();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let height = lines.len();
+        let mut cells = vec![Cell::Empty; width * height];
+        let mut numbers = vec![];
+        let mut symbols = vec![];
+
+        for (row, line) in lines.iter().enumerate() {
+            let bytes = line.as_bytes();
+            let mut col = 0;
+            while col < bytes.len() {
+                let c = bytes[col];
+                if c.is_ascii_digit() {
+                    let start = col;
+                    let mut value = 0;
+                    while col < bytes.len() && bytes[col].is_ascii_digit() {
+                        value = value * 10 + (bytes[col] - b'0') as u32;
+                        cells[row * width + col] = Cell::Digit(numbers.len());
+                        col += 1;
+                    }
+                    numbers.push(Number { value, row, start, end: col });
+                    continue;
+                }
+                if c != b'.' {
+                    cells[row * width + col] = Cell::Symbol(symbols.len());
+                    symbols.push(Symbol { kind: c as char, row, col });
+                }
+                col += 1;
+            }
+        }
+
+        // every number looks at the cells around it once, which finds each
+        // touching symbol exactly once no matter how many digits it borders
+        let mut number_symbols = vec![vec![]; numbers.len()];
+        let mut symbol_numbers = vec![vec![]; symbols.len()];
+        for (i, number) in numbers.iter().enumerate() {
+            let rows = number.row.saturating_sub(1)..(number.row + 2).min(height);
+            for r in rows {
+                for c in number.start.saturating_sub(1)..(number.end + 1).min(width) {
+                    if let Cell::Symbol(j) = cells[r * width + c] {
+                        number_symbols[i].push(j);
+                        symbol_numbers[j].push(i);
+                    }
+                }
+            }
+        }
+        symbol_numbers.iter_mut().for_each(|numbers| numbers.sort_unstable());
+
+        Schematic { numbers, symbols, number_symbols, symbol_numbers }
     }
 
-    fn adjacent_to(&self, other: &Coordinate) -> bool {
-        let x_diff = (self.x as i32 - other.x as i32).abs();
-        let y_diff = (self.y as i32 - other.y as i32).abs();
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
 
-        x_diff <= 1 && y_diff <= 1
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
     }
-}
 
-struct Number {
-    value: u32,
-    positions: Vec<Coordinate>,
-}
+    /// Symbols touching the number with index `i`
+    pub fn symbols_of(&self, i: usize) -> impl Iterator<Item = &Symbol> {
+        self.number_symbols[i].iter().map(|&j| &self.symbols[j])
+    }
 
-impl Number {
-    fn new(value: u32, positions: Vec<Coordinate>) -> Self {
-        Self { value, positions }
+    /// Numbers touching the symbol with index `j`
+    pub fn numbers_of(&self, j: usize) -> impl Iterator<Item = &Number> {
+        self.symbol_numbers[j].iter().map(|&i| &self.numbers[i])
     }
 
-    fn adjacent_to(&self, symbol_position: &Coordinate) -> bool {
-        for pos in &self.positions {
-            if pos.adjacent_to(symbol_position) {
-                return true;
-            }
-        }
-        false
+    /// Numbers adjacent to any symbol, each one once
+    pub fn parts(&self) -> impl Iterator<Item = &Number> {
+        self.numbers.iter()
+            .zip(&self.number_symbols)
+            .filter(|(_, symbols)| !symbols.is_empty())
+            .map(|(number, _)| number)
     }
-}
 
-fn get_symbol_positions(input: &str) -> Vec<Coordinate> {
-    let re = Regex::new(r"[^\d.]").unwrap();
-    input.lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            re.find_iter(line)
-                .map(move |m| Coordinate::new(m.start() as u32, y as u32))
-        })
-        .collect()
-}
+    /// Symbols of the given kind touching exactly `n` numbers, together with those numbers
+    pub fn gears(&self, kind: char, n: usize) -> impl Iterator<Item = (&Symbol, Vec<&Number>)> {
+        self.symbols.iter()
+            .enumerate()
+            .filter(move |(j, symbol)| symbol.kind == kind && self.symbol_numbers[*j].len() == n)
+            .map(|(j, symbol)| (symbol, self.numbers_of(j).collect()))
+    }
 
-fn get_numbers(input: &str) -> Vec<Number> {
-    let re = Regex::new(r"\d+").unwrap();
-    let get_coordinates = |start, length, row| {
-        let mut out = vec![];
-        for i in start..start + length {
-            out.push(Coordinate::new(i, row));
+    /// For every kind of symbol, the sum of the parts touching a symbol of that kind.
+    /// A part touching several kinds counts once for each of them.
+    pub fn sum_by_kind(&self) -> BTreeMap<char, u32> {
+        let mut sums = BTreeMap::new();
+        for (i, number) in self.numbers.iter().enumerate() {
+            let mut kinds = self.symbols_of(i).map(|symbol| symbol.kind).collect::<Vec<_>>();
+            kinds.sort_unstable();
+            kinds.dedup();
+            for kind in kinds {
+                *sums.entry(kind).or_insert(0) += number.value;
+            }
         }
-        out
-    };
-
-    input.lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            re.find_iter(line)
-                .map(move |m| {
-                    let val = m.as_str().parse::<u32>().unwrap();
-                    let start = m.start() as u32;
-                    let length = m.as_str().len() as u32;
-                    Number::new(val, get_coordinates(start, length, y as u32))
-                })
-        })
-        .collect()
+        sums
+    }
 }
 
 pub fn part_one(input: &str) -> u32 {
-    let symbol_positions = get_symbol_positions(input);
-    let numbers = get_numbers(input);
-
-    let mut sum = 0;
-    for num in &numbers {
-        for pos in &symbol_positions {
-            if num.adjacent_to(pos) {
-                sum += num.value;
-            }
-        }
-    }
-    sum
+    Schematic::parse(input)
+        .parts()
+        .map(|part| part.value)
+        .sum()
 }
 
 pub fn part_two(input: &str) -> u32 {
-    let symbol_positions = get_symbol_positions(input);
-    let numbers = get_numbers(input);
-
-    let mut sum = 0;
-    for pos in &symbol_positions {
-        let mut adj_numbers: Vec<&Number> = Vec::new();
-        for num in &numbers {
-            if num.adjacent_to(pos) {
-                adj_numbers.push(num);
-            }
-        }
-
-        if adj_numbers.len() == 2 {
-            sum += adj_numbers[0].value * adj_numbers[1].value;
-        }
-    }
-    sum
+    Schematic::parse(input)
+        .gears('*', 2)
+        .map(|(_, parts)| parts.iter().map(|part| part.value).product::<u32>())
+        .sum()
 }
 
 #[cfg(test)]
@@ -126,4 +171,79 @@ mod tests {
         let input = read_file("inputs", 3);
         assert_eq!(part_two(&input), 75312571);
     }
+
+    #[test]
+    fn test_examples() {
+        let input = read_file("examples", 3);
+        assert_eq!(part_one(&input), 4361);
+        assert_eq!(part_two(&input), 467835);
+    }
+
+    const EXAMPLE: &str = "467..114..\n...*......\n..35..633.";
+
+    #[test]
+    fn test_schematic() {
+        let schematic = Schematic::parse(EXAMPLE);
+        assert_eq!(schematic.numbers().len(), 4);
+        assert_eq!(schematic.numbers()[3], Number { value: 633, row: 2, start: 6, end: 9 });
+        assert_eq!(schematic.symbols(), [Symbol { kind: '*', row: 1, col: 3 }]);
+        let around_star = schematic.numbers_of(0).map(|n| n.value).collect::<Vec<_>>();
+        assert_eq!(around_star, vec![467, 35]);
+        assert_eq!(schematic.symbols_of(1).count(), 0);
+        assert_eq!(schematic.symbols_of(2).next(), Some(&schematic.symbols()[0]));
+    }
+
+    #[test]
+    fn test_parts() {
+        let input = read_file("examples", 3);
+        let schematic = Schematic::parse(&input);
+        let parts = schematic.parts().map(|part| part.value).collect::<Vec<_>>();
+        assert_eq!(parts, vec![467, 35, 633, 617, 592, 755, 664, 598]);
+    }
+
+    #[test]
+    fn test_part_touching_two_symbols_counts_once() {
+        assert_eq!(part_one("1*\n*."), 1);
+        assert_eq!(part_one("*12#"), 12);
+        let schematic = Schematic::parse("*12#");
+        assert_eq!(schematic.symbols_of(0).count(), 2);
+        assert_eq!(schematic.parts().count(), 1);
+    }
+
+    #[test]
+    fn test_gears() {
+        let input = read_file("examples", 3);
+        let schematic = Schematic::parse(&input);
+        let gears = schematic.gears('*', 2)
+            .map(|(symbol, parts)| ((symbol.row, symbol.col), parts.iter().map(|p| p.value).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(gears, vec![((1, 3), vec![467, 35]), ((8, 5), vec![755, 598])]);
+        assert_eq!(schematic.gears('*', 1).count(), 1);
+        assert_eq!(schematic.gears('#', 2).count(), 0);
+
+        // only '*' makes a gear
+        assert_eq!(part_two("12#34"), 0);
+        assert_eq!(Schematic::parse("12#34").gears('#', 2).count(), 1);
+    }
+
+    #[test]
+    fn test_sum_by_kind() {
+        let input = read_file("examples", 3);
+        let sums = Schematic::parse(&input).sum_by_kind();
+        let expected = [('#', 633), ('$', 664), ('*', 467 + 35 + 617 + 755 + 598), ('+', 592)];
+        assert_eq!(sums, BTreeMap::from(expected));
+
+        // one part next to two kinds counts for both, next to two of a kind only once
+        let sums = Schematic::parse("*12#\n..*.").sum_by_kind();
+        assert_eq!(sums, BTreeMap::from([('#', 12), ('*', 12)]));
+    }
+
+    #[test]
+    fn test_edges() {
+        // numbers and symbols on the border of the grid, and ragged lines
+        assert_eq!(part_one("1.\n.#\n22"), 23);
+        assert_eq!(part_one("5\n*"), 5);
+        assert_eq!(part_one("..9\n#"), 0);
+        assert_eq!(part_two("12*34"), 408);
+    }
 }
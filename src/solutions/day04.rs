@@ -1,85 +1,98 @@
-use std::{collections::{HashSet, HashMap}, error::Error};
-use itertools::Itertools;
-use regex::Regex;
+use std::error::Error;
+
+/// Card numbers have to be below this
+const NUMBERS: usize = 128;
+/// A card wins copies of at most `NUMBERS` following cards, so this many slots
+/// of pending copies are enough to never wrap onto a card that is still pending
+const RING: usize = NUMBERS + 1;
+
+/// Set of numbers in `0..NUMBERS`, one bit each
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct NumberSet([u64; NUMBERS / 64]);
+
+impl NumberSet {
+    fn parse(s: &str) -> Result<Self, Box<dyn Error>> {
+        let mut set = NumberSet::default();
+        for number in s.split_whitespace() {
+            let number = number.parse::<usize>()?;
+            if number >= NUMBERS {
+                return Err(format!("card number {} is not below {}", number, NUMBERS).into());
+            }
+            set.0[number / 64] |= 1 << (number % 64);
+        }
+        Ok(set)
+    }
 
+    fn intersection_size(&self, other: &NumberSet) -> u32 {
+        self.0.iter().zip(other.0).map(|(a, b)| (a & b).count_ones()).sum()
+    }
+}
 
 #[derive(Clone, Copy, Debug)]
 struct Card {
     id: u32,
     intersection_size: u32,
-    frequency: u32,
 }
 
 impl Card {
+    /// Parses `Card 1: 41 48 83 | 83 86 6 31`
+    fn parse(line: &str) -> Result<Card, Box<dyn Error>> {
+        let (header, numbers) = line.split_once(':').ok_or("missing ':' after the card id")?;
+        let id = header.strip_prefix("Card").ok_or("line does not start with \"Card\"")?.trim().parse()?;
+        let (winning, mine) = numbers.split_once('|').ok_or("missing '|' between the numbers")?;
+        let intersection_size = NumberSet::parse(winning)?.intersection_size(&NumberSet::parse(mine)?);
+        Ok(Card { id, intersection_size })
+    }
+
     fn get_points(card: Card) -> u32 {
         if card.intersection_size == 0 {
             return 0;
         }
         u32::pow(2, card.intersection_size - 1)
     }
+}
 
-    fn increase_frequency(&mut self, num: u32) {
-        self.frequency += num;
-    }
+/// Copies of the cards still to come, indexed by position modulo `RING`
+struct Cascade {
+    pending: [u32; RING],
+    position: usize,
 }
 
-fn get_set(s: &str) -> HashSet<i32> {
-    let mut hs = HashSet::<i32>::new();
-    let re = Regex::new(r"\d+").expect("invalid regex");
-    for cap in re.captures_iter(s) {
-        hs.insert(cap[0].parse::<i32>().unwrap());
+impl Cascade {
+    fn new() -> Self {
+        Cascade { pending: [0; RING], position: 0 }
     }
-    hs
-}
 
-fn parse_line(line: &str) -> Result<Card, Box<dyn Error>> {
-    let re = Regex::new(r"Card\s*(\d+): *(\d+.*) *\| *(\d+.*)")?;
-    let caps = re.captures(line).ok_or("No captures")?;
+    /// Number of copies of the next card, which it hands on to the cards it wins
+    fn push(&mut self, card: &Card) -> u32 {
+        let copies = 1 + std::mem::take(&mut self.pending[self.position % RING]);
+        for i in 1..=card.intersection_size as usize {
+            self.pending[(self.position + i) % RING] += copies;
+        }
+        self.position += 1;
+        copies
+    }
+}
 
-    let id = caps[1].parse::<u32>()?;
-    let winning_set = get_set(&caps[2]);
-    let my_set = get_set(&caps[3]);
-    let intersection_size = winning_set.intersection(&my_set).count();
+fn parse_cards(input: &str) -> impl Iterator<Item = Card> + '_ {
+    input.lines().map(|line| match Card::parse(line) {
+        Ok(card) => card,
+        Err(e) => panic!("{}", e),
+    })
+}
 
-    Ok(Card { id, intersection_size: intersection_size as u32, frequency: 1})
+/// Card id and how many copies of it end up on the pile, in input order
+pub fn copies(input: &str) -> impl Iterator<Item = (u32, u32)> + '_ {
+    let mut cascade = Cascade::new();
+    parse_cards(input).map(move |card| (card.id, cascade.push(&card)))
 }
 
 pub fn part_one(input: &str) -> u32 {
-    input.lines()
-        .map(|line| {
-            match parse_line(line) {
-                Ok(card) => Card::get_points(card),
-                Err(e) => panic!("{}", e),
-            }
-        })
-        .sum()
+    parse_cards(input).map(Card::get_points).sum()
 }
 
 pub fn part_two(input: &str) -> u32 {
-    let mut cards = input.lines()
-        .map(|line| {
-            let c = match parse_line(line) {
-                Ok(card) => card,
-                Err(e) => panic!("{}", e)
-            };
-            (c.id, c)
-        })
-        .collect::<HashMap<u32, Card>>();
-    // Lessons learned debugging: HashMaps are not sorted
-    let ids: Vec<_> = cards.keys().cloned().sorted().collect();
-    for id in ids {
-        let card = &cards[&id];
-        let freq = card.frequency;
-        let i_size = card.intersection_size;
-        for i in 0..i_size {
-            let target = id + i + 1;
-            if let Some(target_card) = cards.get_mut(&target) {
-                target_card.increase_frequency(freq);
-            }
-        }
-    }
-
-    cards.values().map(|card| card.frequency).sum()
+    copies(input).map(|(_, copies)| copies).sum()
 }
 
 #[cfg(test)]
@@ -100,4 +113,49 @@ mod tests {
         let input = read_file("inputs", 4);
         assert_eq!(part_two(&input), 5422730);
     }
+
+    #[test]
+    fn test_examples() {
+        let input = read_file("examples", 4);
+        assert_eq!(part_one(&input), 13);
+        assert_eq!(part_two(&input), 30);
+    }
+
+    #[test]
+    fn test_copies() {
+        let input = read_file("examples", 4);
+        let copies = copies(&input).collect::<Vec<_>>();
+        assert_eq!(copies, vec![(1, 1), (2, 2), (3, 4), (4, 8), (5, 14), (6, 1)]);
+    }
+
+    #[test]
+    fn test_card_parse() {
+        let card = Card::parse("Card  12: 41 48 83 86 17 | 83 86  6 31 17  9 48 53").unwrap();
+        assert_eq!(card.id, 12);
+        assert_eq!(card.intersection_size, 4);
+        assert_eq!(Card::get_points(card), 8);
+
+        assert!(Card::parse("Card 1: 41 48").is_err());
+        assert!(Card::parse("Card 1: 41 x | 41").is_err());
+        assert!(Card::parse("Card 1: 128 | 1").is_err());
+        assert!(Card::parse("Cart 1: 1 | 1").is_err());
+    }
+
+    #[test]
+    fn test_number_set_word_boundary() {
+        let a = NumberSet::parse("0 63 64 127").unwrap();
+        let b = NumberSet::parse("63 64 100 127").unwrap();
+        assert_eq!(a.intersection_size(&b), 3);
+    }
+
+    #[test]
+    fn test_ring_wraps_around() {
+        // more cards than ring slots, every card wins the one after it
+        let input = (1..=300)
+            .map(|id| format!("Card {}: {} | {}", id, if id < 300 { 1 } else { 2 }, 1))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let copies = copies(&input).map(|(_, copies)| copies).collect::<Vec<_>>();
+        assert_eq!(copies, (1..=300).collect::<Vec<_>>());
+    }
 }
@@ -1,129 +1,386 @@
 use std::error::Error;
 use std::io;
 
-type Src = u32;
-type Dest = u32;
+type Src = u64;
+type Dest = u64;
 
 #[derive(Clone, Copy)]
 struct Range {
     dest_start: Dest,
     src_start: Src,
-    length: u32
+    length: u64
 }
 
 impl Range {
-    fn new(dest_start: Dest, src_start: Src, length: u32) -> Self {
+    fn new(dest_start: Dest, src_start: Src, length: u64) -> Self {
         Range {dest_start, src_start, length}
     }
 
     fn from_line(line: &str) -> Result<Self, io::Error> {
-        let nums: Vec<u32> = line.split(" ")
-            .filter_map(|s| s.parse().ok())
-            .collect();
-        if nums.len() == 3 {
-            Ok(Range::new(nums[0], nums[1], nums[2]))
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Range line does not contain exactly three numbers")
-            )
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let nums = line.split_whitespace()
+            .map(|s| s.parse::<u64>().map_err(|e| invalid(format!("invalid number {:?} in range {:?}: {}", s, line, e))))
+            .collect::<Result<Vec<u64>, io::Error>>()?;
+        if nums.len() != 3 {
+            return Err(invalid(format!("range {:?} does not contain exactly three numbers", line)));
         }
+
+        let range = Range::new(nums[0], nums[1], nums[2]);
+        if range.length == 0 {
+            return Err(invalid(format!("range {:?} is empty", line)));
+        }
+        // both ends are computed from the start and the length,
+        // so they have to fit or mapping would overflow later
+        let last = range.length - 1;
+        if range.src_start.checked_add(last).is_none() || range.dest_start.checked_add(last).is_none() {
+            return Err(invalid(format!("range {:?} overflows u64", line)));
+        }
+        Ok(range)
     }
 
     fn map(&self, src_val: Src) -> Dest {
-        // I assume the caller checked in_range, 
-        // therefore offset is always Some and it is safe to unwrap
+        // I assume the caller checked the value is in range,
+        // therefore offset is always Some and it is safe to unwrap.
+        // from_line made sure the last destination fits, so this cannot overflow
         self.dest_start + self.offset(src_val).unwrap()
     }
 
-    fn in_range(&self, src_val: Src) -> bool {
-        let offset = match self.offset(src_val) {
-            Some(offset) => offset,
-            None => {return false}
-        };
-        offset < self.length
+    fn src_end(&self) -> Src {
+        self.src_start + (self.length - 1)
     }
 
-    fn offset(&self, src_val: Src) -> Option<u32> {
+    fn offset(&self, src_val: Src) -> Option<u64> {
         src_val.checked_sub(self.src_start)
     }
+
+    /// Splits the interval into the part covered by this range (mapped)
+    /// and the parts before and after it (unmapped)
+    fn map_interval(&self, interval: Interval) -> (Option<Interval>, Vec<Interval>) {
+        let start = interval.start.max(self.src_start);
+        let end = interval.end.min(self.src_end());
+        if start > end {
+            return (None, vec![interval]);
+        }
+
+        let mut rest = vec![];
+        if interval.start < start {
+            rest.push(Interval { start: interval.start, end: start - 1 });
+        }
+        if end < interval.end {
+            rest.push(Interval { start: end + 1, end: interval.end });
+        }
+        (Some(Interval { start: self.map(start), end: self.map(end) }), rest)
+    }
+}
+
+/// Closed interval of values, `end` is inclusive so that
+/// ranges ending at `u64::MAX` do not overflow
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub start: Src,
+    pub end: Src
+}
+
+impl Interval {
+    pub fn new(start: Src, length: u64) -> Self {
+        Interval { start, end: start + (length - 1) }
+    }
+
+    /// Like `new`, but rejects empty and overflowing intervals
+    fn checked_new(start: Src, length: u64) -> Result<Self, Box<dyn Error>> {
+        let end = length.checked_sub(1)
+            .and_then(|last| start.checked_add(last))
+            .ok_or_else(|| format!("seed range {} {} is empty or overflows u64", start, length))?;
+        Ok(Interval { start, end })
+    }
+}
+
+/// Values in `start..=end` are shifted by `offset`,
+/// which needs `i128` to cover any shift within `u64`
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Segment {
+    start: Src,
+    end: Src,
+    offset: i128
 }
 
-struct Map {
+impl Segment {
+    fn apply(&self, val: Src) -> Dest {
+        (val as i128 + self.offset) as Dest
+    }
+}
+
+/// A whole map, or a chain of maps, as one piecewise-linear function.
+/// The segments are sorted and cover every value from `0` to `Src::MAX`.
+#[derive(Debug, PartialEq)]
+pub struct Piecewise {
+    segments: Vec<Segment>
+}
+
+impl Piecewise {
+    fn identity() -> Self {
+        Piecewise { segments: vec![Segment { start: 0, end: Src::MAX, offset: 0 }] }
+    }
+
+    fn from_map(map: &Map) -> Self {
+        let mut segments = vec!();
+        let mut pending = vec![Interval { start: 0, end: Src::MAX }];
+        // same splitting as map_intervals, so earlier ranges win on overlaps
+        for range in &map.ranges {
+            let offset = range.dest_start as i128 - range.src_start as i128;
+            let mut unmapped = vec!();
+            for interval in pending {
+                let (hit, rest) = range.map_interval(interval);
+                if let Some(hit) = hit {
+                    let start = (hit.start as i128 - offset) as Src;
+                    let end = (hit.end as i128 - offset) as Src;
+                    segments.push(Segment { start, end, offset });
+                }
+                unmapped.extend(rest);
+            }
+            pending = unmapped;
+        }
+        segments.extend(pending.iter().map(|i| Segment { start: i.start, end: i.end, offset: 0 }));
+        segments.sort_by_key(|segment| segment.start);
+        Piecewise { segments }
+    }
+
+    /// Index of the segment containing `val`
+    fn find(&self, val: Src) -> usize {
+        self.segments.partition_point(|segment| segment.end < val)
+    }
+
+    pub fn apply(&self, val: Src) -> Dest {
+        self.segments[self.find(val)].apply(val)
+    }
+
+    /// The function that applies `self` first and `next` second
+    fn then(&self, next: &Piecewise) -> Piecewise {
+        let mut segments: Vec<Segment> = vec!();
+        for segment in &self.segments {
+            let image_end = segment.apply(segment.end);
+            let mut val = segment.apply(segment.start);
+            let mut i = next.find(val);
+            loop {
+                let end = image_end.min(next.segments[i].end);
+                let offset = segment.offset + next.segments[i].offset;
+                let start = (val as i128 - segment.offset) as Src;
+                let domain_end = (end as i128 - segment.offset) as Src;
+                match segments.last_mut() {
+                    Some(last) if last.offset == offset => last.end = domain_end,
+                    _ => segments.push(Segment { start, end: domain_end, offset })
+                }
+                if end == image_end {
+                    break;
+                }
+                val = end + 1;
+                i += 1;
+            }
+        }
+        Piecewise { segments }
+    }
+
+    /// The function mapping every output back to its input,
+    /// which only exists if every output is hit exactly once
+    pub fn inverse(&self) -> Result<Piecewise, Box<dyn Error>> {
+        let mut segments = self.segments.iter()
+            .map(|segment| Segment {
+                start: segment.apply(segment.start),
+                end: segment.apply(segment.end),
+                offset: -segment.offset
+            })
+            .collect::<Vec<_>>();
+        segments.sort_by_key(|segment| segment.start);
+
+        let tiles = segments.first().map(|first| first.start) == Some(0)
+            && segments.last().map(|last| last.end) == Some(Src::MAX)
+            && segments.windows(2).all(|w| w[0].end.checked_add(1) == Some(w[1].start));
+        if !tiles {
+            return Err("map is not invertible, some values are hit twice or never".into());
+        }
+        Ok(Piecewise { segments })
+    }
+}
+
+/// One `<source>-to-<destination> map:` section of the almanac
+pub struct Map {
+    source: String,
+    destination: String,
     ranges: Vec<Range>
 }
 
 impl Map {
-    fn new(ranges: Vec<Range>) -> Self {
-        Map {ranges}
+    fn new(source: &str, destination: &str, ranges: Vec<Range>) -> Self {
+        Map {source: source.to_string(), destination: destination.to_string(), ranges}
     }
 
-    fn map(&self, src_val: Src) -> Dest {
+    fn from_header(line: &str) -> Result<Self, Box<dyn Error>> {
+        let name = line.strip_suffix(" map:")
+            .ok_or_else(|| format!("invalid map header: {:?}", line))?;
+        let (source, destination) = name.split_once("-to-")
+            .ok_or_else(|| format!("map name is not of the form <source>-to-<destination>: {:?}", name))?;
+        Ok(Map::new(source, destination, vec!()))
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    fn map_intervals(&self, intervals: Vec<Interval>) -> Vec<Interval> {
+        let mut mapped = vec!();
+        let mut pending = intervals;
         for range in &self.ranges {
-            if range.in_range(src_val) {
-                return range.map(src_val);
+            let mut unmapped = vec!();
+            for interval in pending {
+                let (hit, rest) = range.map_interval(interval);
+                mapped.extend(hit);
+                unmapped.extend(rest);
             }
+            pending = unmapped;
         }
-        return src_val;
+        // whatever no range covers maps to itself
+        mapped.extend(pending);
+        mapped
     }
 }
 
-fn parse_seeds(input: &str) -> Result<Vec<u32>, Box<dyn Error>> {
-    let line = input.lines().nth(0).ok_or("")?;
-    Ok(line.split(" ")
-        .into_iter()
-        .filter_map(|s| s.parse().ok())
-        .collect::<Vec<u32>>())
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: Vec<Map>
 }
 
-fn parse_maps(input: &str) -> Vec<Map> {
-    let mut ranges = vec!();
-    let mut curr = vec!(); 
-    let mut skip_next = false;
-    for line in input.lines().skip(3) {
-        if skip_next {
-            skip_next = false;
-            continue;
+impl Almanac {
+    /// Parses the seed line and every map section, regardless of their order
+    /// and of how many blank lines separate them
+    pub fn parse(input: &str) -> Result<Self, Box<dyn Error>> {
+        let mut seeds = None;
+        let mut maps: Vec<Map> = vec!();
+        for line in input.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(numbers) = line.strip_prefix("seeds:") {
+                seeds = Some(parse_numbers(numbers)?);
+            } else if line.ends_with("map:") {
+                let map = Map::from_header(line)?;
+                if maps.iter().any(|other| other.source == map.source) {
+                    return Err(format!("more than one map from category {:?}", map.source).into());
+                }
+                maps.push(map);
+            } else {
+                let map = maps.last_mut()
+                    .ok_or_else(|| format!("range outside of a map: {:?}", line))?;
+                map.ranges.push(Range::from_line(line)?);
+            }
+        }
+        let seeds = seeds.ok_or("almanac has no seeds line")?;
+        Ok(Almanac { seeds, maps })
+    }
+
+    /// The chain of maps that converts category `from` into category `to`
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&Map>, Box<dyn Error>> {
+        let mut path: Vec<&Map> = vec!();
+        let mut curr = from;
+        while curr != to {
+            let map = self.maps.iter()
+                .find(|map| map.source == curr)
+                .ok_or_else(|| format!(
+                    "no map from category {:?}, cannot get from {:?} to {:?}", curr, from, to
+                ))?;
+            if path.iter().any(|seen| seen.source == map.destination) {
+                return Err(format!("category chain from {:?} loops without reaching {:?}", from, to).into());
+            }
+            path.push(map);
+            curr = &map.destination;
         }
-        if line.trim().is_empty() {
-            ranges.push(Map::new(curr));
-            curr = vec!();
-            skip_next = true;
-            continue;
+        Ok(path)
+    }
+
+    /// Converts a single value of category `from` into category `to`
+    pub fn convert(&self, from: &str, to: &str, value: u64) -> Result<u64, Box<dyn Error>> {
+        let intervals = self.convert_intervals(from, to, vec![Interval::new(value, 1)])?;
+        Ok(intervals[0].start)
+    }
+
+    /// All maps between the two categories as a single function
+    pub fn compose(&self, from: &str, to: &str) -> Result<Piecewise, Box<dyn Error>> {
+        Ok(self.path(from, to)?
+            .into_iter()
+            .fold(Piecewise::identity(), |f, map| f.then(&Piecewise::from_map(map))))
+    }
+
+    /// Finds the lowest location reachable from `seeds` by walking
+    /// through the locations in ascending order and mapping them back to seeds
+    pub fn lowest_location_backwards(&self, seeds: &[Interval]) -> Result<u64, Box<dyn Error>> {
+        let inverse = self.compose("seed", "location")?.inverse()?;
+        for segment in &inverse.segments {
+            // the seeds of this segment are contiguous and in the same order as the locations
+            let lowest_seed = seeds.iter()
+                .filter_map(|seed| {
+                    let start = seed.start.max(segment.apply(segment.start));
+                    let end = seed.end.min(segment.apply(segment.end));
+                    (start <= end).then_some(start)
+                })
+                .min();
+            if let Some(seed) = lowest_seed {
+                return Ok((seed as i128 - segment.offset) as u64);
+            }
         }
-        match Range::from_line(line) {
-            Ok(range) => curr.push(range),
-            Err(e) => panic!("{}", e)
+        Err("no seed reaches a location".into())
+    }
+
+    fn convert_intervals(&self, from: &str, to: &str, intervals: Vec<Interval>) -> Result<Vec<Interval>, Box<dyn Error>> {
+        let mut curr = intervals;
+        for map in self.path(from, to)? {
+            curr = map.map_intervals(curr);
         }
+        Ok(curr)
     }
-    // finally add the last map
-    ranges.push(Map::new(curr));
-    ranges
-}
 
-pub fn part_one(input: &str) -> u32 {
-    let seeds = match parse_seeds(input) {
-        Ok(seeds) => seeds,
-        Err(e) => panic!("{}", e)
-    };
-    let maps = parse_maps(input);
-    let mut locations = vec!();
-    for seed in seeds {
-        let mut curr = seed;
-        for map in &maps {
-            curr = map.map(curr);
+    fn seed_ranges(&self) -> Result<Vec<Interval>, Box<dyn Error>> {
+        if !self.seeds.len().is_multiple_of(2) {
+            return Err("seed line does not consist of (start, length) pairs".into());
         }
-        locations.push(curr);
+        self.seeds.chunks(2)
+            .map(|pair| Interval::checked_new(pair[0], pair[1]))
+            .collect()
     }
 
-    match locations.iter().min() {
-        Some(min) => *min,
-        _ => panic!("locations are empty")
+    fn lowest_location(&self, seeds: Vec<Interval>) -> Result<u64, Box<dyn Error>> {
+        self.convert_intervals("seed", "location", seeds)?
+            .iter()
+            .map(|interval| interval.start)
+            .min()
+            .ok_or_else(|| "locations are empty".into())
+    }
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<u64>, Box<dyn Error>> {
+    s.split_whitespace()
+        .map(|n| n.parse().map_err(|e| format!("invalid number {:?}: {}", n, e).into()))
+        .collect()
+}
+
+type SeedParser = fn(&Almanac) -> Result<Vec<Interval>, Box<dyn Error>>;
+
+fn solve(input: &str, seeds: SeedParser) -> u64 {
+    let result = Almanac::parse(input)
+        .and_then(|almanac| almanac.lowest_location(seeds(&almanac)?));
+    match result {
+        Ok(location) => location,
+        Err(e) => panic!("{}", e)
     }
 }
 
-pub fn part_two(input: &str) -> u32 {
-    0
+pub fn part_one(input: &str) -> u64 {
+    solve(input, |almanac| {
+        Ok(almanac.seeds.iter().map(|&seed| Interval::new(seed, 1)).collect())
+    })
+}
+
+pub fn part_two(input: &str) -> u64 {
+    solve(input, Almanac::seed_ranges)
 }
 
 #[cfg(test)]
@@ -141,7 +398,200 @@ mod tests {
 
     #[test]
     fn test_part_two() {
-        let input = read_file("examples", 25);
-        assert_eq!(part_two(&input), 0);
+        let input = read_file("inputs", 5);
+        assert_eq!(part_two(&input), 15880236);
+    }
+
+    #[test]
+    fn test_part_one_example() {
+        let input = read_file("examples", 5);
+        assert_eq!(part_one(&input), 35);
+    }
+
+    #[test]
+    fn test_part_two_example() {
+        let input = read_file("examples", 5);
+        assert_eq!(part_two(&input), 46);
+    }
+
+    #[test]
+    fn test_map_interval_splits_at_range_bounds() {
+        // maps 98..=99 to 50..=51
+        let range = Range::new(50, 98, 2);
+        let (mapped, rest) = range.map_interval(Interval { start: 90, end: 110 });
+        assert_eq!(mapped, Some(Interval { start: 50, end: 51 }));
+        assert_eq!(rest, vec![Interval { start: 90, end: 97 }, Interval { start: 100, end: 110 }]);
+
+        let (mapped, rest) = range.map_interval(Interval { start: 10, end: 20 });
+        assert_eq!(mapped, None);
+        assert_eq!(rest, vec![Interval { start: 10, end: 20 }]);
+    }
+
+    #[test]
+    fn test_map_intervals_keeps_uncovered_values() {
+        let map = Map::new("seed", "soil", vec![Range::new(50, 98, 2), Range::new(52, 50, 48)]);
+        let mut mapped = map.map_intervals(vec![Interval::new(45, 10), Interval::new(99, 3)]);
+        mapped.sort_by_key(|interval| interval.start);
+        assert_eq!(mapped, vec![
+            Interval { start: 45, end: 49 },
+            Interval { start: 51, end: 51 },
+            Interval { start: 52, end: 56 },
+            Interval { start: 100, end: 101 },
+        ]);
+    }
+
+    #[test]
+    fn test_range_ending_at_u64_max() {
+        let range = Range::new(0, u64::MAX - 1, 2);
+        let (mapped, rest) = range.map_interval(Interval::new(u64::MAX - 3, 4));
+        assert_eq!(mapped, Some(Interval { start: 0, end: 1 }));
+        assert_eq!(rest, vec![Interval { start: u64::MAX - 3, end: u64::MAX - 2 }]);
+    }
+
+    const ABOVE_U32: &str = "seeds: 4294967290 7
+
+seed-to-location map:
+5000000000 4294967295 2
+4294967295 5000000000 2
+0 4294967297 1
+4294967297 0 1";
+
+    #[test]
+    fn test_values_across_u32_boundary() {
+        let almanac = Almanac::parse(ABOVE_U32).unwrap();
+        assert_eq!(almanac.convert("seed", "location", 4294967294).unwrap(), 4294967294);
+        assert_eq!(almanac.convert("seed", "location", 4294967295).unwrap(), 5000000000);
+        assert_eq!(almanac.convert("seed", "location", 4294967296).unwrap(), 5000000001);
+        assert_eq!(almanac.convert("seed", "location", 4294967297).unwrap(), 0);
+        assert_eq!(almanac.convert("seed", "location", 4294967298).unwrap(), 4294967298);
+        assert_eq!(part_one(ABOVE_U32), 7);
+        assert_eq!(part_two(ABOVE_U32), 4294967290);
+
+        let composed = almanac.compose("seed", "location").unwrap();
+        assert_eq!(composed.apply(4294967296), 5000000001);
+        assert_eq!(composed.inverse().unwrap().apply(0), 4294967297);
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        assert!(Range::from_line("1 2 3").is_ok());
+        assert!(Range::from_line("18446744073709551615 0 1").is_ok());
+        // destination end past u64::MAX
+        assert!(Range::from_line("18446744073709551615 0 2").is_err());
+        // source end past u64::MAX
+        assert!(Range::from_line("0 18446744073709551615 2").is_err());
+        assert!(Range::from_line("1 2 0").is_err());
+        assert!(Range::from_line("18446744073709551616 0 1").is_err());
+        assert!(Range::from_line("1 -2 3").is_err());
+        assert!(Range::from_line("1 2 3 4").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "seed range 18446744073709551615 2 is empty or overflows u64")]
+    fn test_overflowing_seed_range() {
+        part_two("seeds: 18446744073709551615 2\nseed-to-location map:\n0 1 2");
+    }
+
+    #[test]
+    fn test_parse_named_maps() {
+        let almanac = Almanac::parse(&read_file("examples", 5)).unwrap();
+        assert_eq!(almanac.seeds, vec![79, 14, 55, 13]);
+        let names = almanac.maps.iter()
+            .map(|map| format!("{}-to-{}", map.source(), map.destination()))
+            .collect::<Vec<_>>();
+        assert_eq!(names[0], "seed-to-soil");
+        assert_eq!(names[6], "humidity-to-location");
+    }
+
+    #[test]
+    fn test_path_between_categories() {
+        let almanac = Almanac::parse(&read_file("examples", 5)).unwrap();
+        let path = almanac.path("humidity", "location").unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(almanac.path("seed", "location").unwrap().len(), 7);
+        assert_eq!(almanac.path("water", "water").unwrap().len(), 0);
+        // seed 79 -> soil 81 -> fertilizer 81 -> water 81 -> light 74
+        assert_eq!(almanac.convert("seed", "light", 79).unwrap(), 74);
+        assert_eq!(almanac.convert("seed", "location", 79).unwrap(), 82);
+    }
+
+    #[test]
+    fn test_sections_in_any_order() {
+        let input = read_file("examples", 5);
+        let mut sections = input.split("\n\n").collect::<Vec<_>>();
+        sections.reverse();
+        let shuffled = sections.join("\n\n\n");
+        assert_eq!(part_one(&shuffled), 35);
+        assert_eq!(part_two(&shuffled), 46);
+    }
+
+    #[test]
+    fn test_broken_chain() {
+        let input = read_file("examples", 5).replace("water-to-light", "water-to-lamp");
+        let almanac = Almanac::parse(&input).unwrap();
+        let err = almanac.path("seed", "location").err().expect("chain is broken");
+        assert_eq!(err.to_string(), "no map from category \"lamp\", cannot get from \"seed\" to \"location\"");
+        assert!(almanac.path("location", "seed").is_err());
+    }
+
+    #[test]
+    fn test_invalid_almanac() {
+        assert!(Almanac::parse("seed-to-soil map:\n1 2 3").is_err());
+        assert!(Almanac::parse("seeds: 1 2\n1 2 3").is_err());
+        assert!(Almanac::parse("seeds: 1 2\nseed-soil map:\n1 2 3").is_err());
+        assert!(Almanac::parse("seeds: 1 2\nseed-to-soil map:\n1 2").is_err());
+    }
+
+    #[test]
+    fn test_compose_matches_single_maps() {
+        for file in ["examples", "inputs"] {
+            let almanac = Almanac::parse(&read_file(file, 5)).unwrap();
+            let composed = almanac.compose("seed", "location").unwrap();
+            assert!(composed.segments.windows(2).all(|w| w[0].end + 1 == w[1].start));
+            for &seed in &almanac.seeds {
+                assert_eq!(composed.apply(seed), almanac.convert("seed", "location", seed).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        for file in ["examples", "inputs"] {
+            let almanac = Almanac::parse(&read_file(file, 5)).unwrap();
+            let composed = almanac.compose("seed", "location").unwrap();
+            let inverse = composed.inverse().unwrap();
+            for &seed in &almanac.seeds {
+                assert_eq!(inverse.apply(composed.apply(seed)), seed);
+            }
+            assert_eq!(inverse.inverse().unwrap(), composed);
+        }
+    }
+
+    #[test]
+    fn test_not_invertible() {
+        // 0 and 1 are both mapped to 1, nothing is mapped to 0
+        let map = Map::new("a", "b", vec![Range::new(1, 0, 1)]);
+        assert!(Piecewise::from_map(&map).inverse().is_err());
+    }
+
+    #[test]
+    fn test_lowest_location_backwards() {
+        for (file, expected_one, expected_two) in [("examples", 35, 46), ("inputs", 621354867, 15880236)] {
+            let input = read_file(file, 5);
+            let almanac = Almanac::parse(&input).unwrap();
+            let seeds = almanac.seeds.iter().map(|&seed| Interval::new(seed, 1)).collect::<Vec<_>>();
+            assert_eq!(almanac.lowest_location_backwards(&seeds).unwrap(), expected_one);
+            assert_eq!(part_one(&input), expected_one);
+            let ranges = almanac.seed_ranges().unwrap();
+            assert_eq!(almanac.lowest_location_backwards(&ranges).unwrap(), expected_two);
+            assert_eq!(part_two(&input), expected_two);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "no map from category")]
+    fn test_part_one_broken_chain() {
+        let input = read_file("examples", 5).replace("light-to-temperature map", "light-to-heat map");
+        part_one(&input);
     }
 }
@@ -1,4 +1,5 @@
 use std::error::Error;
+use num::integer::Roots;
 use regex::Regex;
 
 struct Race {
@@ -12,32 +13,41 @@ impl Race {
     }
 
     fn winning(&self, charging: u64) -> bool {
-        (charging * (self.time - charging)) > self.record
+        // charging <= time, so the product is at most (time / 2)^2
+        charging <= self.time && charging * (self.time - charging) > self.record
     }
 
-    fn calculate_wins(&self) -> u32 {
-        let mut fst = 1;
-        let mut snd = self.time - 1;
-        loop {
-            let fst_winning = self.winning(fst);
-            let snd_winning = self.winning(snd);
-
-            if fst_winning && snd_winning {
-                return (snd - fst + 1) as u32;
-            }
-            if !fst_winning {
-                fst += 1;
-            }
-            if !snd_winning {
-                snd -= 1;
-            }
+    /// Counts the charge times c with c * (time - c) > record.
+    /// These lie strictly between the roots (time ± sqrt(time² - 4 * record)) / 2,
+    /// which only need an integer square root and a step of correction each way.
+    fn calculate_wins(&self) -> u64 {
+        let time = self.time as u128;
+        let discriminant = match (time * time).checked_sub(4 * self.record as u128) {
+            Some(d) => d,
+            None => return 0
+        };
+        let root = discriminant.sqrt() as u64;
+
+        // the parabola is symmetric around time / 2, so the last winning
+        // charge time is time - first
+        let mut first = (self.time - root.min(self.time)) / 2;
+        while first > 0 && self.winning(first - 1) {
+            first -= 1;
+        }
+        while first <= self.time / 2 && !self.winning(first) {
+            first += 1;
+        }
+        if !self.winning(first) {
+            return 0;
         }
+        let last = self.time - first;
+        last - first + 1
     }
 }
 
 fn parse_part_one(input: &str) -> Result<Vec<Race>, Box<dyn Error>> {
-    let first = input.lines().nth(0).unwrap();
-    let second = input.lines().nth(1).unwrap();
+    let first = input.lines().next().ok_or("No first line")?;
+    let second = input.lines().nth(1).ok_or("No second line")?;
 
     let re = Regex::new(r"(\d+)")?;
     let first_numbers: Vec<u64> = re.find_iter(first)
@@ -48,7 +58,7 @@ fn parse_part_one(input: &str) -> Result<Vec<Race>, Box<dyn Error>> {
         .collect();
 
     let races: Vec<Race> = first_numbers.into_iter()
-        .zip(second_numbers.into_iter())
+        .zip(second_numbers)
         .map(|(time, record)| Race::new(time, record))
         .collect();
 
@@ -57,7 +67,7 @@ fn parse_part_one(input: &str) -> Result<Vec<Race>, Box<dyn Error>> {
 
 fn parse_part_two(input: &str) -> Result<Race, Box<dyn Error>> {
     let re = Regex::new(r"Time:\s*((\d+\s*)+)")?;
-    let first_line = input.lines().nth(0).ok_or("No first line")?;
+    let first_line = input.lines().next().ok_or("No first line")?;
     let cap = re.captures(first_line).ok_or("No captures")?;
     let time_string = cap[1].replace(" ", "");
     
@@ -72,7 +82,7 @@ fn parse_part_two(input: &str) -> Result<Race, Box<dyn Error>> {
     Ok(Race::new(time, record))
 }
 
-pub fn part_one(input: &str) -> u32 {
+pub fn part_one(input: &str) -> u64 {
     let races = match parse_part_one(input) {
         Ok(races) => races,
         Err(e) => panic!("Error parsing input: {}", e)
@@ -83,7 +93,7 @@ pub fn part_one(input: &str) -> u32 {
         .product()
 }
 
-pub fn part_two(input: &str) -> u32 {
+pub fn part_two(input: &str) -> u64 {
     match parse_part_two(input) {
         Ok(race) => race.calculate_wins(),
         Err(e) => panic!("Error parsing input: {}", e)
@@ -108,4 +118,43 @@ mod tests {
         let input = read_file("inputs", 6);
         assert_eq!(part_two(&input), 28360140);
     }
+
+    #[test]
+    fn test_examples() {
+        let input = read_file("examples", 6);
+        assert_eq!(part_one(&input), 288);
+        assert_eq!(part_two(&input), 71503);
+    }
+
+    fn count_wins_naive(race: &Race) -> u64 {
+        (0..=race.time).filter(|&c| race.winning(c)).count() as u64
+    }
+
+    #[test]
+    fn test_calculate_wins_matches_naive() {
+        for time in 0..60 {
+            for record in 0..(time * time / 4 + 3) {
+                let race = Race::new(time, record);
+                assert_eq!(race.calculate_wins(), count_wins_naive(&race), "time {} record {}", time, record);
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_winning_time() {
+        // the best charge time only ties the record
+        assert_eq!(Race::new(4, 4).calculate_wins(), 0);
+        assert_eq!(Race::new(0, 0).calculate_wins(), 0);
+        assert_eq!(Race::new(10, 1_000).calculate_wins(), 0);
+    }
+
+    #[test]
+    fn test_large_race() {
+        // time^2 does not fit a u64, the discriminant has to be computed in u128
+        let time = u32::MAX as u64;
+        let race = Race::new(time, 0);
+        assert_eq!(race.calculate_wins(), time - 1);
+        let race = Race::new(time, (time / 2) * (time - time / 2) - 1);
+        assert_eq!(race.calculate_wins(), 2);
+    }
 }
@@ -1,5 +1,9 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
-enum Card {
+pub enum Card {
     Joker,
     Two,
     Three,
@@ -10,15 +14,16 @@ enum Card {
     Eight,
     Nine,
     Ten,
+    Jack,
     Queen,
     King,
     Ace
 }
 
 impl Card {
-    fn from_char(c: char) -> Option<Card> {
+    fn from_char(c: char, ruleset: Ruleset) -> Option<Card> {
         match c {
-            'J' => Some(Card::Joker),
+            'J' => Some(ruleset.jack()),
             '2' => Some(Card::Two),
             '3' => Some(Card::Three),
             '4' => Some(Card::Four),
@@ -34,10 +39,58 @@ impl Card {
             _ => None
         }
     }
+
+    fn to_char(self) -> char {
+        match self {
+            Card::Joker | Card::Jack => 'J',
+            Card::Two => '2',
+            Card::Three => '3',
+            Card::Four => '4',
+            Card::Five => '5',
+            Card::Six => '6',
+            Card::Seven => '7',
+            Card::Eight => '8',
+            Card::Nine => '9',
+            Card::Ten => 'T',
+            Card::Queen => 'Q',
+            Card::King => 'K',
+            Card::Ace => 'A'
+        }
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
+/// The rules differ only in what `J` stands for. Since `Card::Joker`
+/// sorts below `Card::Two` and `Card::Jack` sits between ten and queen,
+/// this also decides how cards order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ruleset {
+    /// `J` is a jack, there are no wildcards
+    Standard,
+    /// `J` is a joker, the weakest card, and counts as whatever makes the best hand
+    JokersWild
+}
+
+impl Ruleset {
+    fn jack(&self) -> Card {
+        match self {
+            Ruleset::Standard => Card::Jack,
+            Ruleset::JokersWild => Card::Joker
+        }
+    }
+
+    fn is_wild(&self, card: Card) -> bool {
+        *self == Ruleset::JokersWild && card == Card::Joker
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPair,
@@ -48,8 +101,8 @@ enum HandType {
 }
 
 impl HandType {
-    fn from_cards(cards: [Card; 5]) -> HandType {
-        let (freq, jokers) = frequencies(cards);
+    fn from_cards(cards: [Card; 5], ruleset: Ruleset) -> HandType {
+        let (freq, jokers) = frequencies(cards, ruleset);
         let (fst, snd) = (freq[0], freq[1]);
         match (fst + jokers, snd) {
             (5, _) => HandType::FiveOfAKind,
@@ -63,53 +116,203 @@ impl HandType {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-struct Hand {
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            HandType::HighCard => "high card",
+            HandType::OnePair => "one pair",
+            HandType::TwoPair => "two pair",
+            HandType::ThreeOfAKind => "three of a kind",
+            HandType::FullHouse => "full house",
+            HandType::FourOfAKind => "four of a kind",
+            HandType::FiveOfAKind => "five of a kind"
+        };
+        f.pad(name)
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Hand {
     hand_type: HandType,
     cards: [Card; 5],
-    bet: u32
+    bet: u32,
+    substitute: Option<Card>
 }
 
-fn frequencies(cards: [Card; 5]) -> ([u32; 13], u32) {
-    let mut card_frequencies = [0; 13];
-    let mut jokers = 0;
-    for &card in cards.iter() {
-        match card {
-            Card::Joker => {jokers += 1;},
-            _ => {card_frequencies[card as usize] += 1;}
+impl Hand {
+    /// Parses `KTJJT 220` or just `KTJJT`, in which case the bet is 0
+    pub fn parse(line: &str, ruleset: Ruleset) -> Result<Hand, Box<dyn Error>> {
+        let (cards, bet) = line.split_once(' ').unwrap_or((line, "0"));
+        let cards = cards.chars()
+            .map(|c| Card::from_char(c, ruleset).ok_or_else(|| format!("invalid card {:?}", c)))
+            .collect::<Result<Vec<Card>, String>>()?;
+        let cards: [Card; 5] = cards.try_into()
+            .map_err(|_| format!("hand {:?} does not have five cards", line))?;
+        let bet = bet.trim().parse::<u32>().map_err(|e| format!("invalid bet {:?}: {}", bet, e))?;
+        Ok(Hand {
+            hand_type: HandType::from_cards(cards, ruleset),
+            cards,
+            bet,
+            substitute: best_substitute(cards, ruleset)
+        })
+    }
+
+    pub fn hand_type(&self) -> HandType {
+        self.hand_type
+    }
+
+    pub fn cards(&self) -> [Card; 5] {
+        self.cards
+    }
+
+    pub fn bet(&self) -> u32 {
+        self.bet
+    }
+
+    /// Position and stand-in card of every wildcard in the hand
+    pub fn substitutions(&self) -> Vec<(usize, Card)> {
+        match self.substitute {
+            Some(card) => self.cards.iter()
+                .enumerate()
+                .filter(|(_, &c)| c == Card::Joker)
+                .map(|(i, _)| (i, card))
+                .collect(),
+            None => vec![]
+        }
+    }
+
+    /// Compares like `Ord`, but also says what decided the comparison
+    pub fn compare(&self, other: &Hand) -> Comparison {
+        if self.hand_type != other.hand_type {
+            return Comparison {
+                ordering: self.hand_type.cmp(&other.hand_type),
+                decider: Decider::HandType(self.hand_type, other.hand_type)
+            };
+        }
+        let differing = self.cards.iter().zip(other.cards.iter()).enumerate().find(|(_, (a, b))| a != b);
+        match differing {
+            Some((position, (&ours, &theirs))) => Comparison {
+                ordering: ours.cmp(&theirs),
+                decider: Decider::Card { position, ours, theirs }
+            },
+            None => Comparison { ordering: Ordering::Equal, decider: Decider::Identical }
+        }
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for card in self.cards {
+            write!(f, "{}", card)?;
+        }
+        Ok(())
+    }
+}
+
+/// What settled the comparison of two hands
+#[derive(PartialEq, Eq, Debug)]
+pub enum Decider {
+    HandType(HandType, HandType),
+    /// The first position (0-based) at which the cards differ
+    Card { position: usize, ours: Card, theirs: Card },
+    Identical
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct Comparison {
+    pub ordering: Ordering,
+    pub decider: Decider
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verb = match self.ordering {
+            Ordering::Less => "loses to",
+            Ordering::Equal => "ties with",
+            Ordering::Greater => "beats"
         };
+        match self.decider {
+            Decider::HandType(ours, theirs) => write!(f, "{} {} {}", ours, verb, theirs),
+            Decider::Card { position, ours, theirs } => {
+                write!(f, "{} {} {} at card {}", ours, verb, theirs, position + 1)
+            },
+            Decider::Identical => write!(f, "identical cards")
+        }
     }
-    card_frequencies.sort_unstable_by(|a, b| b.cmp(a));  // descending order
-    (card_frequencies, jokers)
 }
 
-fn parse_hand(line: &str) -> Hand {
-    let (cards, bet) = line.split_once(" ").expect("invalid line format");
-    let mut parsed_cards = [Card::Ace; 5];
-    for (i, c) in cards.chars().enumerate() {
-        parsed_cards[i] = Card::from_char(c).expect("invalid card");
+/// The card every wildcard turns into: the most frequent other card,
+/// the higher one on ties, or an ace if the hand is all wildcards
+fn best_substitute(cards: [Card; 5], ruleset: Ruleset) -> Option<Card> {
+    if !cards.iter().any(|&card| ruleset.is_wild(card)) {
+        return None;
     }
-    let hand_type = HandType::from_cards(parsed_cards);
-    let bet = bet.parse::<u32>().expect("invalid bet");
-    Hand {
-        cards: parsed_cards,
-        hand_type,
-        bet
+    let count = |card: &Card| cards.iter().filter(|&c| c == card).count();
+    let best = cards.iter()
+        .filter(|&&card| !ruleset.is_wild(card))
+        .max_by_key(|&card| (count(card), *card));
+    Some(best.copied().unwrap_or(Card::Ace))
+}
+
+fn frequencies(cards: [Card; 5], ruleset: Ruleset) -> ([u32; 14], u32) {
+    let mut card_frequencies = [0; 14];
+    let mut jokers = 0;
+    for &card in cards.iter() {
+        if ruleset.is_wild(card) {
+            jokers += 1;
+        } else {
+            card_frequencies[card as usize] += 1;
+        }
     }
+    card_frequencies.sort_unstable_by(|a, b| b.cmp(a));  // descending order
+    (card_frequencies, jokers)
 }
 
 fn calculate_total(hands: &[Hand]) -> u32 {
     hands.iter().enumerate().map(|(position, hand)| (position + 1) as u32 * hand.bet).sum()
 }
 
+/// All hands from weakest to strongest
+pub fn ranked_hands(input: &str, ruleset: Ruleset) -> Vec<Hand> {
+    let mut hands = input.lines()
+        .map(|line| match Hand::parse(line, ruleset) {
+            Ok(hand) => hand,
+            Err(e) => panic!("{}", e)
+        })
+        .collect::<Vec<_>>();
+    hands.sort();
+    hands
+}
+
+/// One line per hand in rank order, with the reason it beats the hand below
+pub fn explain(input: &str, ruleset: Ruleset) -> String {
+    let hands = ranked_hands(input, ruleset);
+    let mut out = String::new();
+    for (i, hand) in hands.iter().enumerate() {
+        let mut line = format!("{:>5}  {}  {:<15}  bet {:>4}", i + 1, hand, hand.hand_type(), hand.bet());
+        let substitutions = hand.substitutions();
+        if let Some((_, card)) = substitutions.first() {
+            line += &format!("  (J as {})", card);
+        }
+        if i > 0 {
+            line += &format!("  {} over {}", hand.compare(&hands[i - 1]), hands[i - 1]);
+        }
+        out += line.trim_end();
+        out.push('\n');
+    }
+    out
+}
+
+fn solve(input: &str, ruleset: Ruleset) -> u32 {
+    calculate_total(&ranked_hands(input, ruleset))
+}
+
 pub fn part_one(input: &str) -> u32 {
-    0
+    solve(input, Ruleset::Standard)
 }
 
 pub fn part_two(input: &str) -> u32 {
-    let mut hands = input.lines().map(parse_hand).collect::<Vec<_>>();
-    hands.sort();
-    calculate_total(&hands)
+    solve(input, Ruleset::JokersWild)
 }
 
 #[cfg(test)]
@@ -131,10 +334,42 @@ mod tests {
         assert_eq!(part_two(&input), 249817836);
     }
 
+    #[test]
+    fn test_part_one_example() {
+        let input = read_file("examples", 7);
+        assert_eq!(part_one(&input), 6440);
+    }
+
+    #[test]
+    fn test_part_two_example() {
+        let input = read_file("examples", 7);
+        assert_eq!(part_two(&input), 5905);
+    }
+
     #[test]
     fn card_ordering() {
         assert!(Card::Two < Card::Three);
         assert!(Card::Three < Card::Four);
+        assert!(Card::Ten < Card::Jack);
+        assert!(Card::Jack < Card::Queen);
+        assert!(Card::Joker < Card::Two);
+    }
+
+    #[test]
+    fn ruleset_parses_jack() {
+        assert_eq!(Card::from_char('J', Ruleset::Standard), Some(Card::Jack));
+        assert_eq!(Card::from_char('J', Ruleset::JokersWild), Some(Card::Joker));
+        assert_eq!(Card::from_char('Q', Ruleset::JokersWild), Some(Card::Queen));
+        assert_eq!(Card::from_char('X', Ruleset::Standard), None);
+    }
+
+    #[test]
+    fn ruleset_wildcards() {
+        let cards = [Card::King, Card::Ten, Card::Jack, Card::Jack, Card::Ten];
+        assert!(HandType::from_cards(cards, Ruleset::Standard) == HandType::TwoPair);
+        let cards = [Card::King, Card::Ten, Card::Joker, Card::Joker, Card::Ten];
+        assert!(HandType::from_cards(cards, Ruleset::JokersWild) == HandType::FourOfAKind);
+        assert!(HandType::from_cards([Card::Joker; 5], Ruleset::JokersWild) == HandType::FiveOfAKind);
     }
 
     #[test]
@@ -149,13 +384,80 @@ mod tests {
         let hand1 = Hand {
             cards: [Card::Ace, Card::Ace, Card::Two, Card::Three, Card::Four],
             hand_type: HandType::OnePair,
-            bet: 1
+            bet: 1,
+            substitute: None
         };
         let hand2 = Hand {
             cards: [Card::Two, Card::Two, Card::Two, Card::Two, Card::Two],
             hand_type: HandType::FiveOfAKind,
-            bet: 2
+            bet: 2,
+            substitute: None
         };
         assert!(hand1 < hand2);
     }
+
+    #[test]
+    fn parse_hand_under_ruleset() {
+        let hand = Hand::parse("KTJJT 220", Ruleset::Standard).unwrap();
+        assert_eq!(hand.hand_type(), HandType::TwoPair);
+        assert_eq!(hand.bet(), 220);
+        assert_eq!(hand.substitutions(), vec![]);
+
+        let hand = Hand::parse("KTJJT", Ruleset::JokersWild).unwrap();
+        assert_eq!(hand.hand_type(), HandType::FourOfAKind);
+        assert_eq!(hand.bet(), 0);
+        assert_eq!(hand.substitutions(), vec![(2, Card::Ten), (3, Card::Ten)]);
+        assert_eq!(hand.to_string(), "KTJJT");
+    }
+
+    #[test]
+    fn substitutions() {
+        // ties go to the higher card
+        let hand = Hand::parse("2K2KJ", Ruleset::JokersWild).unwrap();
+        assert_eq!(hand.substitutions(), vec![(4, Card::King)]);
+        assert_eq!(hand.hand_type(), HandType::FullHouse);
+
+        let hand = Hand::parse("JJJJJ", Ruleset::JokersWild).unwrap();
+        assert_eq!(hand.substitutions().len(), 5);
+        assert_eq!(hand.hand_type(), HandType::FiveOfAKind);
+    }
+
+    #[test]
+    fn invalid_hands() {
+        assert!(Hand::parse("KTJJ 1", Ruleset::Standard).is_err());
+        assert!(Hand::parse("KTJJTT 1", Ruleset::Standard).is_err());
+        assert!(Hand::parse("KTJJX 1", Ruleset::Standard).is_err());
+        assert!(Hand::parse("KTJJT x", Ruleset::Standard).is_err());
+    }
+
+    #[test]
+    fn compare_explains_decider() {
+        let kk677 = Hand::parse("KK677", Ruleset::Standard).unwrap();
+        let ktjjt = Hand::parse("KTJJT", Ruleset::Standard).unwrap();
+        let comparison = kk677.compare(&ktjjt);
+        assert_eq!(comparison.ordering, Ordering::Greater);
+        assert_eq!(comparison.decider, Decider::Card { position: 1, ours: Card::King, theirs: Card::Ten });
+        assert_eq!(comparison.to_string(), "K beats T at card 2");
+
+        let qqqja = Hand::parse("QQQJA", Ruleset::JokersWild).unwrap();
+        let kk677 = Hand::parse("KK677", Ruleset::JokersWild).unwrap();
+        let comparison = kk677.compare(&qqqja);
+        assert_eq!(comparison.ordering, Ordering::Less);
+        assert_eq!(comparison.to_string(), "two pair loses to four of a kind");
+
+        assert_eq!(kk677.compare(&kk677).decider, Decider::Identical);
+    }
+
+    #[test]
+    fn explain_ranked_list() {
+        let input = read_file("examples", 7);
+        let explanation = explain(&input, Ruleset::JokersWild);
+        let lines = explanation.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "    1  32T3K  one pair         bet  765");
+        assert!(lines[4].starts_with("    5  KTJJT  four of a kind   bet  220  (J as T)  K beats Q at card 1 over QQQJA"));
+        for (comparison, hands) in ranked_hands(&input, Ruleset::JokersWild).windows(2).map(|w| (w[1].compare(&w[0]), w)) {
+            assert_eq!(comparison.ordering, hands[1].cmp(&hands[0]));
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use num::Integer;
+
+use crate::cycle::{self, Cycle};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instruction {
+    Left,
+    Right
+}
+
+impl Instruction {
+    fn from_char(c: char) -> Result<Self, Box<dyn Error>> {
+        match c {
+            'L' => Ok(Instruction::Left),
+            'R' => Ok(Instruction::Right),
+            _ => Err(format!("invalid instruction {:?}", c).into())
+        }
+    }
+}
+
+/// The instructions and the node table, with nodes referred to by index
+pub struct Network {
+    instructions: Vec<Instruction>,
+    names: Vec<String>,
+    /// Left and right neighbour of every node
+    nodes: Vec<(usize, usize)>
+}
+
+impl Network {
+    pub fn parse(input: &str) -> Result<Self, Box<dyn Error>> {
+        let mut lines = input.lines();
+        let instructions = lines.next()
+            .ok_or("missing instructions")?
+            .trim()
+            .chars()
+            .map(Instruction::from_char)
+            .collect::<Result<Vec<_>, _>>()?;
+        if instructions.is_empty() {
+            return Err("missing instructions".into());
+        }
+
+        let mut table = vec![];
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let (name, neighbours) = line.split_once('=').ok_or_else(|| format!("missing '=' in {:?}", line))?;
+            let (left, right) = neighbours.trim()
+                .strip_prefix('(')
+                .and_then(|s| s.strip_suffix(')'))
+                .and_then(|s| s.split_once(','))
+                .ok_or_else(|| format!("expected \"(<left>, <right>)\" in {:?}", line))?;
+            table.push((name.trim(), left.trim(), right.trim()));
+        }
+
+        let names = table.iter().map(|(name, _, _)| name.to_string()).collect::<Vec<_>>();
+        let index = names.iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect::<HashMap<_, _>>();
+        let lookup = |name: &str| index.get(name).copied().ok_or_else(|| format!("unknown node {:?}", name));
+        let nodes = table.iter()
+            .map(|(_, left, right)| Ok((lookup(left)?, lookup(right)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Network { instructions, names, nodes })
+    }
+
+    fn node(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// The node reached from `node` by the instruction used at step `i`
+    fn step(&self, node: usize, i: usize) -> usize {
+        match self.instructions[i % self.instructions.len()] {
+            Instruction::Left => self.nodes[node].0,
+            Instruction::Right => self.nodes[node].1
+        }
+    }
+
+    /// Number of steps from `from` to `to`, or `None` if `to` is never reached
+    pub fn steps(&self, from: &str, to: &str) -> Option<u64> {
+        let (mut node, target) = (self.node(from)?, self.node(to)?);
+        let mut i = 0;
+        // every (node, instruction) state shows up at most once before the walk repeats
+        while node != target {
+            if i > self.nodes.len() * self.instructions.len() {
+                return None;
+            }
+            node = self.step(node, i);
+            i += 1;
+        }
+        Some(i as u64)
+    }
+
+    /// A ghost for every node ending in `A`
+    pub fn ghosts(&self) -> Vec<Ghost> {
+        self.names.iter()
+            .enumerate()
+            .filter(|(_, name)| name.ends_with('A'))
+            .map(|(start, _)| self.ghost(start))
+            .collect()
+    }
+
+    fn ghost(&self, start: usize) -> Ghost {
+        let len = self.instructions.len();
+        // the state is the node together with the position in the instructions
+        let cycle = cycle::find(&(start, 0), |(node, i)| {
+            *node = self.step(*node, *i);
+            *i = (*i + 1) % len;
+        });
+
+        let mut hits = vec![];
+        let mut node = start;
+        for t in 0..cycle.start + cycle.length {
+            if self.names[node].ends_with('Z') {
+                hits.push(t);
+            }
+            node = self.step(node, t);
+        }
+        Ghost { name: self.names[start].clone(), cycle, hits }
+    }
+}
+
+/// Where a ghost's walk starts repeating, and the steps at which it stands on a `Z` node
+/// up to the end of the first pass through the cycle. After that the hits repeat every
+/// `cycle.length` steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ghost {
+    pub name: String,
+    pub cycle: Cycle,
+    pub hits: Vec<usize>
+}
+
+impl Ghost {
+    fn hits_in_cycle(&self) -> impl Iterator<Item = usize> + '_ {
+        self.hits.iter().copied().filter(|&t| t >= self.cycle.start)
+    }
+
+    fn is_on_z(&self, t: u64) -> bool {
+        if t < self.cycle.start as u64 {
+            return self.hits.contains(&(t as usize));
+        }
+        self.hits_in_cycle().any(|hit| t >= hit as u64 && (t - hit as u64).is_multiple_of(self.cycle.length as u64))
+    }
+
+    /// The puzzle's inputs are built so that every ghost reaches its only `Z` node
+    /// exactly at multiples of its cycle length, which is what makes the LCM correct
+    pub fn lcm_applies(&self) -> bool {
+        self.hits == [self.cycle.length] && self.cycle.start <= self.cycle.length
+    }
+}
+
+/// Solves `x = a (mod m)` for all pairs, returning `(x, lcm of all m)`.
+/// `None` if there is no solution or the lcm does not fit in an i128.
+fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    let mut result = (0, 1);
+    for &(a, m) in congruences {
+        let (x, n) = result;
+        let e = n.extended_gcd(&m);
+        if (a - x) % e.gcd != 0 {
+            return None;
+        }
+        let lcm = (n / e.gcd).checked_mul(m)?;
+        // x + n * k = a (mod m), with k = (a - x) / g * inverse of n / g modulo m / g
+        let k = ((a - x) / e.gcd).checked_mul(e.x)?.rem_euclid(m / e.gcd);
+        result = (x.checked_add(n.checked_mul(k)?)?.rem_euclid(lcm), lcm);
+    }
+    Some(result)
+}
+
+/// Least common multiple of all cycle lengths, `None` if it does not fit in a u64
+fn checked_lcm(ghosts: &[Ghost]) -> Option<u64> {
+    ghosts.iter().try_fold(1u64, |acc, ghost| {
+        let length = ghost.cycle.length as u64;
+        (acc / acc.gcd(&length)).checked_mul(length)
+    })
+}
+
+/// The first step at which every ghost stands on a `Z` node at once,
+/// `None` if that never happens or the step does not fit in a u64
+pub fn ghost_steps(ghosts: &[Ghost]) -> Option<u64> {
+    if ghosts.iter().all(Ghost::lcm_applies) {
+        // if the lcm overflows, the CRT below works it out in i128 instead
+        if let Some(lcm) = checked_lcm(ghosts) {
+            return Some(lcm);
+        }
+    }
+
+    // a hit before some ghost's cycle only happens once, so just try them all
+    let before_cycle = ghosts.iter()
+        .flat_map(|ghost| ghost.hits.iter().filter(|&&t| t < ghost.cycle.start))
+        .map(|&t| t as u64)
+        .filter(|&t| ghosts.iter().all(|ghost| ghost.is_on_z(t)))
+        .min();
+    if before_cycle.is_some() {
+        return before_cycle;
+    }
+
+    // otherwise pick one hit in every cycle and line them up with the CRT
+    let mut best: Option<u64> = None;
+    let mut choice = vec![0; ghosts.len()];
+    let in_cycle = ghosts.iter().map(|ghost| ghost.hits_in_cycle().collect::<Vec<_>>()).collect::<Vec<_>>();
+    if in_cycle.iter().any(Vec::is_empty) {
+        return None;
+    }
+    loop {
+        let congruences = ghosts.iter()
+            .zip(&choice)
+            .enumerate()
+            .map(|(g, (ghost, &c))| (in_cycle[g][c] as i128, ghost.cycle.length as i128))
+            .collect::<Vec<_>>();
+        if let Some((x, lcm)) = crt(&congruences) {
+            // the smallest solution that every ghost has reached with its chosen hit
+            let latest = congruences.iter().map(|&(a, _)| a).max().unwrap_or(0);
+            let t = if x >= latest { x } else { x + Integer::div_ceil(&(latest - x), &lcm) * lcm };
+            if let Ok(t) = u64::try_from(t) {
+                best = Some(best.map_or(t, |b| b.min(t)));
+            }
+        }
+
+        // next combination of hits
+        let mut g = 0;
+        while g < choice.len() {
+            choice[g] += 1;
+            if choice[g] < in_cycle[g].len() {
+                break;
+            }
+            choice[g] = 0;
+            g += 1;
+        }
+        if g == choice.len() {
+            return best;
+        }
+    }
+}
+
+fn parse(input: &str) -> Network {
+    match Network::parse(input) {
+        Ok(network) => network,
+        Err(e) => panic!("{}", e)
+    }
+}
+
+pub fn part_one(input: &str) -> u64 {
+    parse(input).steps("AAA", "ZZZ").expect("ZZZ is never reached")
+}
+
+pub fn part_two(input: &str) -> u64 {
+    ghost_steps(&parse(input).ghosts()).expect("the ghosts are never all on Z nodes at once")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{read_file, read_file_with_name};
+    use crate::rng::Lcg;
+
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let input = read_file("examples", 8);
+        assert_eq!(part_one(&input), 6);
+        let input = "RL\n\nAAA = (BBB, CCC)\nBBB = (DDD, EEE)\nCCC = (ZZZ, GGG)\nDDD = (DDD, DDD)\nEEE = (EEE, EEE)\nGGG = (GGG, GGG)\nZZZ = (ZZZ, ZZZ)";
+        assert_eq!(part_one(input), 2);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = read_file_with_name("examples", "08_2");
+        assert_eq!(part_two(&input), 6);
+        let ghosts = parse(&input).ghosts();
+        assert_eq!(ghosts.len(), 2);
+        assert!(ghosts[0].lcm_applies());
+        // 22Z comes around every 3 steps, but the instructions only line up again after 6,
+        // so there are two hits per cycle and the answer comes from the CRT instead
+        assert_eq!(ghosts[1].cycle, Cycle { start: 1, length: 6 });
+        assert_eq!(ghosts[1].hits, vec![3, 6]);
+        assert!(!ghosts[1].lcm_applies());
+    }
+
+    /// Moves all ghosts in lockstep until they are all on a Z node
+    fn lockstep(input: &str, limit: usize) -> Option<u64> {
+        let network = parse(input);
+        let mut nodes = network.ghosts().iter().map(|ghost| network.node(&ghost.name).unwrap()).collect::<Vec<_>>();
+        for t in 0..limit {
+            if nodes.iter().all(|&node| network.names[node].ends_with('Z')) {
+                return Some(t as u64);
+            }
+            nodes.iter_mut().for_each(|node| *node = network.step(*node, t));
+        }
+        None
+    }
+
+    // 1A reaches 1Z after 2 steps and then every 2 steps,
+    // 2A reaches 2Z after 1 step and then every 3 steps
+    const OFFSET: &str = "L\n\n1A = (1B, 1B)\n1B = (1Z, 1Z)\n1Z = (1B, 1B)\n2A = (2Z, 2Z)\n2Z = (2B, 2B)\n2B = (2C, 2C)\n2C = (2Z, 2Z)";
+
+    #[test]
+    fn test_ghost_cycles() {
+        let ghosts = parse(OFFSET).ghosts();
+        assert_eq!(ghosts[0], Ghost { name: "1A".to_string(), cycle: Cycle { start: 1, length: 2 }, hits: vec![2] });
+        assert_eq!(ghosts[1], Ghost { name: "2A".to_string(), cycle: Cycle { start: 1, length: 3 }, hits: vec![1] });
+        assert!(ghosts[0].lcm_applies());
+        assert!(!ghosts[1].lcm_applies());
+    }
+
+    #[test]
+    fn test_crt_fallback() {
+        // the LCM of the cycle lengths would say 6
+        assert_eq!(part_two(OFFSET), 4);
+        assert_eq!(lockstep(OFFSET, 100), Some(4));
+    }
+
+    #[test]
+    fn test_hit_before_cycle() {
+        // 3A passes 3Z once and then loops on 3B forever
+        let input = "L\n\n1A = (1Z, 1Z)\n1Z = (1A, 1A)\n3A = (3Z, 3Z)\n3Z = (3B, 3B)\n3B = (3B, 3B)";
+        assert_eq!(part_two(input), 1);
+
+        let input = "L\n\n1A = (1B, 1B)\n1B = (1Z, 1Z)\n1Z = (1A, 1A)\n3A = (3Z, 3Z)\n3Z = (3B, 3B)\n3B = (3B, 3B)";
+        assert_eq!(ghost_steps(&parse(input).ghosts()), None);
+        assert_eq!(lockstep(input, 100), None);
+    }
+
+    #[test]
+    fn test_several_hits_per_cycle() {
+        // 1A is on a Z node at 1, 3, 5, ... and 2A at 2, 3, 5, 6, 8, ...
+        let input = "L\n\n1A = (1Z, 1Z)\n1Z = (1A, 1A)\n2A = (2B, 2B)\n2B = (2YZ, 2YZ)\n2YZ = (2Z, 2Z)\n2Z = (2B, 2B)";
+        assert_eq!(ghost_steps(&parse(input).ghosts()), lockstep(input, 100));
+        assert_eq!(part_two(input), 3);
+    }
+
+    #[test]
+    fn test_matches_lockstep_on_random_networks() {
+        let mut rng = Lcg::new(12345);
+        let names = ["1A", "2A", "3A", "BZ", "CZ", "DD", "EE", "FF"];
+        for _ in 0..200 {
+            let instructions = (0..1 + rng.below(3)).map(|_| if rng.below(2) == 0 { 'L' } else { 'R' }).collect::<String>();
+            let table = names.iter()
+                .map(|name| format!("{} = ({}, {})", name, names[rng.below(8)], names[rng.below(8)]))
+                .collect::<Vec<_>>();
+            let input = format!("{}\n\n{}", instructions, table.join("\n"));
+            let expected = ghost_steps(&parse(&input).ghosts());
+            assert_eq!(expected, lockstep(&input, 100_000), "{}", input);
+        }
+    }
+
+    fn aligned_ghost(length: usize) -> Ghost {
+        Ghost { name: format!("{}A", length), cycle: Cycle { start: 1, length }, hits: vec![length] }
+    }
+
+    #[test]
+    fn test_large_cycles() {
+        // pairwise coprime
+        let lengths = [2_000_001, 2_000_002, 2_000_003, 2_000_005];
+        let ghosts = lengths.map(aligned_ghost);
+        assert!(ghosts.iter().all(Ghost::lcm_applies));
+        let product = lengths[..3].iter().map(|&l| l as u64).product::<u64>();
+        assert_eq!(checked_lcm(&ghosts[..3]), Some(product));
+        assert_eq!(ghost_steps(&ghosts[..3]), Some(product));
+
+        // the product of all four is about 2^84
+        assert_eq!(checked_lcm(&ghosts), None);
+        assert_eq!(ghost_steps(&ghosts), None);
+
+        // shared factors do not count twice
+        let ghosts = [aligned_ghost(1 << 40), aligned_ghost(1 << 41), aligned_ghost(3 << 40)];
+        assert_eq!(ghost_steps(&ghosts), Some(3 << 41));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(0, 4), (2, 6)]), Some((8, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(0, 1 << 100), (1, (1 << 100) - 1)]), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Network::parse("LX\n\nAAA = (AAA, AAA)").is_err());
+        assert!(Network::parse("L\n\nAAA = (BBB, AAA)").is_err());
+        assert!(Network::parse("L\n\nAAA (AAA, AAA)").is_err());
+        assert!(Network::parse("").is_err());
+    }
+}
@@ -1,106 +1,135 @@
-use std::iter::FromIterator;
-use std::ops::Index;
+use num::{BigInt, BigRational, One, ToPrimitive, Zero};
+use rayon::prelude::*;
 
-struct Seq {
-    seq: Vec<i32>
+/// A sequence reduced to the first and last value of each difference level.
+/// That is all extrapolation needs, so the levels themselves are never stored.
+pub struct History {
+    len: usize,
+    leading: Vec<i64>,
+    trailing: Vec<i64>
 }
 
-impl FromIterator<i32> for Seq {
-    fn from_iter<I: IntoIterator<Item=i32>>(iter: I) -> Self {
-        let seq = iter.into_iter().collect();
-        Seq { seq }
-    }
-}
-
-impl Index<usize> for Seq {
-    type Output = i32;
-
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.seq[index]
-    }
-}
-
-impl Seq {
-    fn from_line(line: &str) -> Self {
-        let seq = line.split_whitespace()
-            .map(|s| s.parse::<i32>().unwrap())
+impl History {
+    pub fn from_line(line: &str) -> Self {
+        let values = line.split_whitespace()
+            .map(|s| s.parse::<i64>().unwrap())
             .collect();
-        Seq { seq }
+        History::reduce(values)
     }
 
-    fn diff(&self) -> Seq {
-        self.seq
-        .windows(2)
-        .map(|w| w[1] - w[0])
-        .collect()
+    /// Differences the values in place, one level at a time, until a level is all zeros
+    fn reduce(mut values: Vec<i64>) -> Self {
+        let len = values.len();
+        let mut leading = vec![];
+        let mut trailing = vec![];
+        let mut level = values.as_mut_slice();
+        while level.iter().any(|&x| x != 0) {
+            leading.push(level[0]);
+            trailing.push(level[level.len() - 1]);
+            for i in 0..level.len() - 1 {
+                level[i] = level[i + 1].checked_sub(level[i]).expect("difference overflows i64");
+            }
+            let shorter = level.len() - 1;
+            level = &mut level[..shorter];
+        }
+        History { len, leading, trailing }
     }
 
-    fn is_zero(&self) -> bool {
-        self.seq.iter().all(|&x| x == 0)
+    /// The value right after the last one: the sum of the last value of every level
+    pub fn next(&self) -> i64 {
+        self.trailing.iter()
+            .try_fold(0i64, |acc, &x| acc.checked_add(x))
+            .expect("prediction overflows i64")
     }
 
-    fn last(&self) -> Option<&i32> {
-        self.seq.last()
+    /// The value right before the first one: the alternating sum of the first value of every level
+    pub fn previous(&self) -> i64 {
+        self.leading.iter()
+            .rev()
+            .try_fold(0i64, |acc, &x| x.checked_sub(acc))
+            .expect("prediction overflows i64")
     }
 
-}
-
-struct History {
-    sequences: Vec<Seq>,
-    complete: bool
-}
-
-impl History {
-    fn new(seq: Seq) -> Self {
-        History { sequences: vec![seq], complete: false}
+    /// The first value of every difference level, which are the
+    /// coefficients of the sequence in Newton's forward form:
+    /// value(x) = sum of leading[j] * binomial(x, j)
+    fn leading(&self) -> impl Iterator<Item = i64> + '_ {
+        self.leading.iter().copied()
     }
 
-    fn step(&mut self) {
-        let new_seq = self.sequences.last().unwrap().diff();
-        self.complete = new_seq.is_zero();
-        self.sequences.push(new_seq);
+    /// The value at position `x`, where 0 is the first known value.
+    /// Works for any `x`, including negative ones and ones far past the end.
+    pub fn value_at(&self, x: i64) -> BigInt {
+        self.evaluate(&BigInt::from(x))
     }
 
-    fn predict_right(&self) -> i32 {
-        let mut curr = 0;
-        for seq in self.sequences.iter().rev().skip(1) {
-            curr = seq.last().unwrap() + curr;
+    fn evaluate(&self, x: &BigInt) -> BigInt {
+        let mut binomial = BigInt::one();
+        let mut value = BigInt::zero();
+        for (j, leading) in self.leading().enumerate() {
+            value += &binomial * leading;
+            // binomial(x, j + 1) = binomial(x, j) * (x - j) / (j + 1), which divides exactly
+            binomial = binomial * (x - j) / (j + 1);
         }
-        curr
+        value
     }
 
-    fn predict_left(&self) -> i32 {
-        let mut curr = 0;
-        for seq in self.sequences.iter().rev().skip(1) {
-            curr = seq[0] - curr;
-        }
-        curr
+    /// The value `k` steps after the last known one
+    pub fn predict_right(&self, k: u64) -> BigInt {
+        let last = BigInt::from(self.len) - 1;
+        self.evaluate(&(last + k))
     }
-}
 
-fn solve(input: &str, predict_fn: fn(&History) -> i32) -> u32 {
-    let mut histories = input.lines()
-        .map(Seq::from_line)
-        .map(History::new)
-        .collect::<Vec<History>>();
+    /// The value `k` steps before the first known one
+    pub fn predict_left(&self, k: u64) -> BigInt {
+        self.evaluate(&-BigInt::from(k))
+    }
 
-    while histories.iter().any(|history| !history.complete) {
-        for history in histories.iter_mut() {
-            if !history.complete {
-                history.step();
+    /// Coefficients of the polynomial through all known values,
+    /// `coefficients()[i]` belongs to `x^i` with `x = 0` at the first value
+    pub fn coefficients(&self) -> Vec<BigRational> {
+        let mut coefficients = vec![BigRational::zero()];
+        // falling factorial x * (x - 1) * ... * (x - j + 1) and j!
+        let mut falling = vec![BigInt::one()];
+        let mut factorial = BigInt::one();
+        for (j, leading) in self.leading().enumerate() {
+            if j > 0 {
+                factorial *= j;
+            }
+            coefficients.resize(falling.len(), BigRational::zero());
+            for (c, f) in coefficients.iter_mut().zip(&falling) {
+                *c += BigRational::new(f * leading, factorial.clone());
             }
+            // multiply by (x - j)
+            let mut next = vec![BigInt::zero(); falling.len() + 1];
+            for (i, f) in falling.iter().enumerate() {
+                next[i + 1] += f;
+                next[i] -= f * j;
+            }
+            falling = next;
+        }
+        while coefficients.len() > 1 && coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
         }
+        coefficients
     }
+}
 
-    histories.iter().map(predict_fn).sum::<i32>().try_into().unwrap()
+fn solve(input: &str, predict_fn: fn(&History) -> i64) -> i64 {
+    input.par_lines()
+        .map(History::from_line)
+        .map(|history| predict_fn(&history) as i128)
+        .sum::<i128>()
+        .to_i64()
+        .expect("sum of predictions does not fit in i64")
 }
 
-pub fn part_one(input: &str) -> u32 {
-    solve(input, History::predict_right)
+pub fn part_one(input: &str) -> i64 {
+    solve(input, History::next)
 }
 
-pub fn part_two(input: &str) -> u32 {
-    solve(input, History::predict_left)
+pub fn part_two(input: &str) -> i64 {
+    solve(input, History::previous)
 }
 
 #[cfg(test)]
@@ -121,4 +150,112 @@ mod tests {
         let input = read_file("inputs", 9);
         assert_eq!(part_two(&input), 925);
     }
+
+    #[test]
+    fn test_examples() {
+        let input = read_file("examples", 9);
+        assert_eq!(part_one(&input), 114);
+        assert_eq!(part_two(&input), 2);
+    }
+
+    #[test]
+    fn test_negative_total() {
+        assert_eq!(part_one("0 -1 -2\n5 3 1"), -4);
+        assert_eq!(part_two("0 -1 -2\n5 3 1"), 8);
+    }
+
+    fn next_value(values: &[i64]) -> i64 {
+        if values.iter().all(|&v| v == 0) {
+            return 0;
+        }
+        let diffs = values.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+        values[values.len() - 1] + next_value(&diffs)
+    }
+
+    #[test]
+    fn test_predict_k_steps() {
+        let mut values = vec![10, 13, 16, 21, 30, 45];
+        let history = History::from_line("10 13 16 21 30 45");
+        assert_eq!(history.predict_right(0), BigInt::from(45));
+        assert_eq!(history.predict_left(1), BigInt::from(5));
+        for k in 1..20 {
+            values.push(next_value(&values));
+            assert_eq!(history.predict_right(k), BigInt::from(*values.last().unwrap()));
+        }
+
+        let mut reversed = vec![45, 30, 21, 16, 13, 10];
+        for k in 1..20 {
+            reversed.push(next_value(&reversed));
+            assert_eq!(history.predict_left(k), BigInt::from(*reversed.last().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_reduce_keeps_boundary_values() {
+        let history = History::from_line("10 13 16 21 30 45");
+        assert_eq!(history.len, 6);
+        assert_eq!(history.leading, vec![10, 3, 0, 2]);
+        assert_eq!(history.trailing, vec![45, 15, 6, 2]);
+        assert_eq!(history.next(), 68);
+        assert_eq!(history.previous(), 5);
+        assert_eq!(history.next(), history.predict_right(1).to_i64().unwrap());
+        assert_eq!(history.previous(), history.predict_left(1).to_i64().unwrap());
+
+        let history = History::from_line("0 0 0");
+        assert_eq!(history.leading, vec![]);
+        assert_eq!(history.next(), 0);
+
+        // no level becomes all zeros before the values run out
+        let history = History::from_line("1 2 4 8 16");
+        assert_eq!(history.leading, vec![1, 1, 1, 1, 1]);
+        assert_eq!(history.next(), 31);
+    }
+
+    #[test]
+    #[should_panic(expected = "prediction overflows i64")]
+    fn test_prediction_overflow() {
+        History::from_line("0 4611686018427387904 9223372036854775807").next();
+    }
+
+    #[test]
+    fn test_far_extrapolation_is_exact() {
+        // x^3 at x = 0..=4
+        let history = History::from_line("0 1 8 27 64");
+        let k = 1_000_000_000_000u64;
+        let x = BigInt::from(4 + k);
+        assert_eq!(history.predict_right(k), &x * &x * &x);
+        assert_eq!(history.predict_left(k), -BigInt::from(k).pow(3));
+        assert_eq!(history.predict_right(u64::MAX), (BigInt::from(u64::MAX) + 4u32).pow(3));
+    }
+
+    #[test]
+    fn test_coefficients() {
+        // 2x^2 - 3x + 1
+        let history = History::from_line("1 0 3 10 21");
+        let expected = [1, -3, 2].map(|c| BigRational::from_integer(BigInt::from(c)));
+        assert_eq!(history.coefficients(), expected);
+
+        // x(x+1)/2 has non-integer coefficients
+        let history = History::from_line("0 1 3 6 10 15");
+        let half = BigRational::new(BigInt::one(), BigInt::from(2));
+        assert_eq!(history.coefficients(), vec![BigRational::zero(), half.clone(), half]);
+
+        let history = History::from_line("7 7 7");
+        assert_eq!(history.coefficients(), vec![BigRational::from_integer(BigInt::from(7))]);
+    }
+
+    #[test]
+    fn test_coefficients_match_values() {
+        let input = read_file("inputs", 9);
+        for line in input.lines().take(20) {
+            let history = History::from_line(line);
+            let coefficients = history.coefficients();
+            for x in [-3i64, 0, 7, 25] {
+                let value = coefficients.iter().rev().fold(BigRational::zero(), |acc, c| {
+                    acc * BigRational::from_integer(BigInt::from(x)) + c
+                });
+                assert_eq!(value, BigRational::from_integer(history.value_at(x)));
+            }
+        }
+    }
 }
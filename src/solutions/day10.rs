@@ -0,0 +1,180 @@
+use std::error::Error;
+
+use crate::polygon::{Direction, Polygon};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+    }
+}
+
+/// The two directions a pipe connects, `None` for ground and the start
+fn connections(tile: u8) -> Option<[Direction; 2]> {
+    match tile {
+        b'|' => Some([Direction::Up, Direction::Down]),
+        b'-' => Some([Direction::Left, Direction::Right]),
+        b'L' => Some([Direction::Up, Direction::Right]),
+        b'J' => Some([Direction::Up, Direction::Left]),
+        b'7' => Some([Direction::Down, Direction::Left]),
+        b'F' => Some([Direction::Down, Direction::Right]),
+        _ => None,
+    }
+}
+
+fn pipe(a: Direction, b: Direction) -> u8 {
+    *b"|-LJ7F".iter()
+        .find(|&&tile| connections(tile).is_some_and(|c| c.contains(&a) && c.contains(&b)))
+        .expect("two different directions always make a pipe")
+}
+
+struct Maze {
+    tiles: Vec<Vec<u8>>,
+    start: (i64, i64),
+}
+
+impl Maze {
+    fn parse(input: &str) -> Result<Self, Box<dyn Error>> {
+        let tiles = input.lines().map(|line| line.as_bytes().to_vec()).collect::<Vec<_>>();
+        let start = tiles.iter()
+            .enumerate()
+            .find_map(|(y, row)| row.iter().position(|&tile| tile == b'S').map(|x| (x as i64, y as i64)))
+            .ok_or("no start tile")?;
+        let mut maze = Maze { tiles, start };
+        let shape = maze.start_shape()?;
+        maze.tiles[start.1 as usize][start.0 as usize] = shape;
+        Ok(maze)
+    }
+
+    fn get(&self, (x, y): (i64, i64)) -> u8 {
+        if x < 0 || y < 0 {
+            return b'.';
+        }
+        self.tiles.get(y as usize).and_then(|row| row.get(x as usize)).copied().unwrap_or(b'.')
+    }
+
+    fn neighbour((x, y): (i64, i64), direction: Direction) -> (i64, i64) {
+        let (dx, dy) = direction.delta();
+        (x + dx, y + dy)
+    }
+
+    /// The pipe under `S`, which has to connect to exactly the two neighbours pointing back at it
+    fn start_shape(&self) -> Result<u8, Box<dyn Error>> {
+        let connected = DIRECTIONS.iter()
+            .copied()
+            .filter(|&direction| {
+                let tile = self.get(Maze::neighbour(self.start, direction));
+                connections(tile).is_some_and(|c| c.contains(&opposite(direction)))
+            })
+            .collect::<Vec<_>>();
+        match connected[..] {
+            [a, b] => Ok(pipe(a, b)),
+            _ => Err(format!("start connects to {} pipes instead of 2", connected.len()).into()),
+        }
+    }
+
+    /// The tiles of the loop through the start, in walking order
+    fn find_loop(&self) -> Vec<(i64, i64)> {
+        let mut tiles = vec![self.start];
+        let mut direction = connections(self.get(self.start)).expect("start is a pipe")[0];
+        let mut position = Maze::neighbour(self.start, direction);
+        while position != self.start {
+            tiles.push(position);
+            let [a, b] = connections(self.get(position)).expect("the loop only has pipes");
+            direction = if a == opposite(direction) { b } else { a };
+            position = Maze::neighbour(position, direction);
+        }
+        tiles
+    }
+}
+
+fn parse(input: &str) -> Maze {
+    match Maze::parse(input) {
+        Ok(maze) => maze,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+pub fn part_one(input: &str) -> u32 {
+    (parse(input).find_loop().len() / 2) as u32
+}
+
+/// The loop is a lattice polygon through the centres of its tiles, so by Pick's theorem
+/// the tiles strictly inside are the interior points of that polygon. Squeezing between
+/// pipes makes no difference, there is no tile there.
+pub fn part_two(input: &str) -> u32 {
+    Polygon::from_points(parse(input).find_loop()).interior_points() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{read_file, read_file_with_name};
+
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let input = read_file("examples", 10);
+        assert_eq!(part_one(&input), 8);
+        assert_eq!(part_one(".....\n.S-7.\n.|.|.\n.L-J.\n....."), 4);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = read_file_with_name("examples", "10_2");
+        assert_eq!(part_two(&input), 10);
+        assert_eq!(part_two(&read_file("examples", 10)), 1);
+    }
+
+    /// Counts enclosed tiles row by row: crossing a pipe that goes up flips inside and outside
+    fn enclosed_by_parity(input: &str) -> u32 {
+        let maze = parse(input);
+        let on_loop = maze.find_loop();
+        let mut count = 0;
+        for (y, row) in maze.tiles.iter().enumerate() {
+            let mut inside = false;
+            for x in 0..row.len() {
+                let position = (x as i64, y as i64);
+                if on_loop.contains(&position) {
+                    if connections(maze.get(position)).is_some_and(|c| c.contains(&Direction::Up)) {
+                        inside = !inside;
+                    }
+                } else if inside {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    const SQUEEZE: &str = "..........\n.S------7.\n.|F----7|.\n.||....||.\n.||....||.\n.|L-7F-J|.\n.|..||..|.\n.L--JL--J.\n..........";
+
+    #[test]
+    fn test_squeezing_between_pipes() {
+        // the tiles between the two inner pipes are outside, only 4 are enclosed
+        assert_eq!(part_two(SQUEEZE), 4);
+        assert_eq!(enclosed_by_parity(SQUEEZE), 4);
+        let input = read_file_with_name("examples", "10_2");
+        assert_eq!(enclosed_by_parity(&input), part_two(&input));
+    }
+
+    #[test]
+    fn test_start_shape() {
+        assert_eq!(parse(&read_file("examples", 10)).get((0, 2)), b'F');
+        assert_eq!(parse(&read_file_with_name("examples", "10_2")).get((4, 0)), b'7');
+        assert_eq!(parse(SQUEEZE).get((1, 1)), b'F');
+        // a neighbour that does not point back at S is not part of the loop
+        assert_eq!(parse("7S-7\n.|.|\n.L-J").get((1, 0)), b'F');
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Maze::parse("...\n...").is_err());
+        assert!(Maze::parse(".|.\n-S-\n.|.").is_err());
+        assert!(Maze::parse("S..\n...").is_err());
+    }
+}
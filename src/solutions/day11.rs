@@ -2,16 +2,17 @@ use core::panic;
 
 use grid::Grid;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Coordinate {
-    x: usize,
-    y: usize
+    x: u64,
+    y: u64
 }
 
+// only the pairwise oracle in the tests measures single distances
+#[cfg(test)]
 impl Coordinate {
-    fn manhatten_distance(&self, other: &Coordinate) -> usize {
-        let x_dist = (self.x as i32 - other.x as i32).abs();
-        let y_dist = (self.y as i32 - other.y as i32).abs();
-        (x_dist + y_dist) as usize
+    fn manhatten_distance(&self, other: &Coordinate) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
     }
 }
 
@@ -45,37 +46,16 @@ impl Universe {
         Universe { grid, galaxies: vec![] }
     }
 
-    fn expand(&mut self) {
-        let mut empty_row_indices = vec![];
-        for (i, mut row) in self.grid.iter_rows().enumerate() {
-            if row.all(Symbol::is_dot) {
-                empty_row_indices.push(i);
-            }
-        }
-
-        let mut empty_col_indices = vec![];
-        for (i, mut col) in self.grid.iter_cols().enumerate() {
-            if col.all(Symbol::is_dot) {
-                empty_col_indices.push(i);
-            }
-        }
-
-        for i in empty_row_indices.iter().rev() {
-            self.insert_empty(*i, "row")
-        }
-
-        for i in empty_col_indices.iter().rev() {
-            self.insert_empty(*i, "col")
-        }
-    }
+    /// Moves every galaxy as if each empty row and column were `factor` wide,
+    /// without inserting anything into the grid
+    fn expand(&mut self, factor: u64) {
+        let empty_rows = empty_before(self.grid.iter_rows().map(|mut row| row.all(Symbol::is_dot)));
+        let empty_cols = empty_before(self.grid.iter_cols().map(|mut col| col.all(Symbol::is_dot)));
 
-    fn insert_empty(&mut self, index: usize, rc: &str) {
-        match rc {
-            "row" => self.grid.insert_row(index, vec![Symbol::Dot; self.grid.cols()]),
-            "col" => self.grid.insert_col(index, vec![Symbol::Dot; self.grid.rows()]),
-            _ => panic!("custom: invalid row/col identifier")
+        for galaxy in self.galaxies.iter_mut() {
+            galaxy.x += (factor - 1) * empty_rows[galaxy.x as usize];
+            galaxy.y += (factor - 1) * empty_cols[galaxy.y as usize];
         }
-        
     }
 
     fn find_galaxies(&mut self) {
@@ -84,22 +64,41 @@ impl Universe {
                 if symbol != Symbol::Hashtag {
                     continue;
                 }
-                self.galaxies.push(Coordinate {x: i, y: j});
+                self.galaxies.push(Coordinate {x: i as u64, y: j as u64});
             }
         }
     }
 
-    fn sum_of_distances(&self) -> u32 {
-        let mut sum = 0;
-        for i in 0..self.galaxies.len() {
-            for j in i+1..self.galaxies.len() {
-                let galaxy1 = &self.galaxies[i];
-                let galaxy2 = &self.galaxies[j];
-                sum += galaxy1.manhatten_distance(&galaxy2) as u32;
-            }
-        }
-        sum
+    /// Manhattan distance splits into x and y, and along one axis
+    /// the sorted value at index i is the larger one in exactly i pairs
+    fn sum_of_distances(&self) -> u64 {
+        let xs = self.galaxies.iter().map(|galaxy| galaxy.x).collect();
+        let ys = self.galaxies.iter().map(|galaxy| galaxy.y).collect();
+        sum_of_differences(xs) + sum_of_differences(ys)
+    }
+}
+
+/// Number of empty lines before each line
+fn empty_before(is_empty: impl Iterator<Item = bool>) -> Vec<u64> {
+    is_empty
+        .scan(0, |count, empty| {
+            let before = *count;
+            *count += empty as u64;
+            Some(before)
+        })
+        .collect()
+}
+
+/// Sum of |a - b| over all pairs of values
+fn sum_of_differences(mut values: Vec<u64>) -> u64 {
+    values.sort_unstable();
+    let mut prefix = 0;
+    let mut sum = 0;
+    for (i, &value) in values.iter().enumerate() {
+        sum += value * i as u64 - prefix;
+        prefix += value;
     }
+    sum
 }
 
 fn parse_input(input: &str) -> Grid<Symbol> {
@@ -111,16 +110,21 @@ fn parse_input(input: &str) -> Grid<Symbol> {
     grid
 }
 
-pub fn part_one(input: &str) -> u32 {
+/// Sum of all pairwise galaxy distances when every empty row and column is `factor` wide
+pub fn solve(input: &str, factor: u64) -> u64 {
     let grid = parse_input(input);
     let mut universe = Universe::new(grid);
-    universe.expand();
     universe.find_galaxies();
+    universe.expand(factor);
     universe.sum_of_distances()
 }
 
-pub fn part_two(input: &str) -> u32 {
-    0
+pub fn part_one(input: &str) -> u64 {
+    solve(input, 2)
+}
+
+pub fn part_two(input: &str) -> u64 {
+    solve(input, 1_000_000)
 }
 
 #[cfg(test)]
@@ -138,8 +142,52 @@ mod tests {
 
     #[test]
     fn test_part_two() {
-        let input = read_file("examples", 25);
-        assert_eq!(part_two(&input), 0);
+        let input = read_file("inputs", 11);
+        assert_eq!(part_two(&input), 625243292686);
+    }
+
+    #[test]
+    fn test_expansion_factors() {
+        let input = read_file("examples", 11);
+        assert_eq!(part_one(&input), 374);
+        assert_eq!(solve(&input, 10), 1030);
+        assert_eq!(solve(&input, 100), 8410);
+    }
+
+    fn sum_of_distances_pairwise(galaxies: &[Coordinate]) -> u64 {
+        let mut sum = 0;
+        for i in 0..galaxies.len() {
+            for j in i+1..galaxies.len() {
+                sum += galaxies[i].manhatten_distance(&galaxies[j]);
+            }
+        }
+        sum
+    }
+
+    #[test]
+    fn test_sum_of_distances_matches_pairwise() {
+        for (file, factor) in [("examples", 2), ("examples", 1_000_000), ("inputs", 2), ("inputs", 1_000_000)] {
+            let mut universe = Universe::new(parse_input(&read_file(file, 11)));
+            universe.find_galaxies();
+            universe.expand(factor);
+            assert_eq!(universe.sum_of_distances(), sum_of_distances_pairwise(&universe.galaxies));
+        }
+    }
+
+    #[test]
+    fn test_expand_remaps_galaxies() {
+        let mut universe = Universe::new(parse_input("#..\n...\n..#"));
+        universe.find_galaxies();
+        universe.expand(5);
+        assert_eq!(universe.galaxies, vec![Coordinate { x: 0, y: 0 }, Coordinate { x: 6, y: 6 }]);
+        // the grid itself is left alone
+        assert_eq!(universe.grid.rows(), 3);
+    }
+
+    #[test]
+    fn test_empty_before() {
+        let empty = [false, true, true, false, true];
+        assert_eq!(empty_before(empty.into_iter()), vec![0, 0, 1, 2, 2]);
     }
 
     #[test]
@@ -163,8 +211,8 @@ mod tests {
 
     #[test]
     fn test_symbol_is_dot() {
-        assert_eq!(Symbol::is_dot(&Symbol::Dot), true);
-        assert_eq!(Symbol::is_dot(&Symbol::Hashtag), false);
+        assert!(Symbol::is_dot(&Symbol::Dot));
+        assert!(!Symbol::is_dot(&Symbol::Hashtag));
     }
 
     #[test]
@@ -0,0 +1,179 @@
+use std::error::Error;
+
+use rayon::prelude::*;
+
+use crate::memo::Memo;
+
+/// One row of the condition records, e.g. `???.### 1,1,3`
+struct Record {
+    springs: Vec<u8>,
+    groups: Vec<usize>
+}
+
+impl Record {
+    fn from_line(line: &str) -> Result<Self, Box<dyn Error>> {
+        let (springs, groups) = line.split_once(' ').ok_or_else(|| format!("missing groups in {:?}", line))?;
+        if let Some(c) = springs.chars().find(|c| !".#?".contains(*c)) {
+            return Err(format!("invalid spring {:?} in {:?}", c, line).into());
+        }
+        let groups = groups.split(',')
+            .map(|group| group.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()?;
+        if groups.contains(&0) {
+            return Err(format!("empty group in {:?}", line).into());
+        }
+        Ok(Record { springs: springs.as_bytes().to_vec(), groups })
+    }
+
+    /// The springs `times` times over joined by `?`, and the groups `times` times over
+    fn unfold(&self, times: usize) -> Record {
+        let mut springs = self.springs.clone();
+        for _ in 1..times {
+            springs.push(b'?');
+            springs.extend(&self.springs);
+        }
+        Record { springs, groups: self.groups.repeat(times) }
+    }
+
+    /// Number of ways to fill in the unknown springs so that the damaged ones form the groups.
+    /// The state is the position in the row, the index of the current group and
+    /// how many damaged springs of that group are already placed.
+    fn arrangements(&self) -> u64 {
+        let springs = &self.springs;
+        let groups = &self.groups;
+        let count = |count: &mut dyn FnMut((usize, usize, usize)) -> u64, (pos, group, run): (usize, usize, usize)| {
+            if pos == springs.len() {
+                let done = (run == 0 && group == groups.len())
+                    || (group + 1 == groups.len() && run == groups[group]);
+                return done as u64;
+            }
+            let mut total = 0;
+            if springs[pos] != b'#' {
+                // operational: ends the current group, which has to be complete
+                if run == 0 {
+                    total += count((pos + 1, group, 0));
+                } else if run == groups[group] {
+                    total += count((pos + 1, group + 1, 0));
+                }
+            }
+            if springs[pos] != b'.' && group < groups.len() && run < groups[group] {
+                // damaged: grows the current group
+                total += count((pos + 1, group, run + 1));
+            }
+            total
+        };
+
+        let longest = groups.iter().max().copied().unwrap_or(0);
+        let mut memo = Memo::dense((springs.len() + 1, groups.len() + 1, longest + 1));
+        memo.solve((0, 0, 0), &count)
+    }
+}
+
+fn solve(input: &str, times: usize) -> u64 {
+    input.par_lines()
+        .map(|line| match Record::from_line(line) {
+            Ok(record) => record.unfold(times).arrangements(),
+            Err(e) => panic!("{}", e)
+        })
+        .sum()
+}
+
+pub fn part_one(input: &str) -> u64 {
+    solve(input, 1)
+}
+
+pub fn part_two(input: &str) -> u64 {
+    solve(input, 5)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::read_file;
+    use crate::rng::Lcg;
+
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let input = read_file("examples", 12);
+        assert_eq!(part_one(&input), 21);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = read_file("examples", 12);
+        assert_eq!(part_two(&input), 525152);
+    }
+
+    #[test]
+    fn test_example_lines() {
+        let input = read_file("examples", 12);
+        let counts = input.lines()
+            .map(|line| Record::from_line(line).unwrap())
+            .map(|record| (record.arrangements(), record.unfold(5).arrangements()))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![(1, 1), (4, 16384), (1, 1), (1, 16), (4, 2500), (10, 506250)]);
+    }
+
+    /// Tries every way of filling in the unknown springs
+    fn brute_force(record: &Record) -> u64 {
+        let unknown = record.springs.iter().filter(|&&c| c == b'?').count();
+        let mut total = 0;
+        for mask in 0u32..1 << unknown {
+            let mut bit = 0;
+            let filled = record.springs.iter()
+                .map(|&c| if c != b'?' { c } else {
+                    bit += 1;
+                    if mask >> (bit - 1) & 1 == 1 { b'#' } else { b'.' }
+                })
+                .collect::<Vec<_>>();
+            let groups = filled.split(|&c| c == b'.')
+                .filter(|run| !run.is_empty())
+                .map(|run| run.len())
+                .collect::<Vec<_>>();
+            total += (groups == record.groups) as u64;
+        }
+        total
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let input = read_file("examples", 12);
+        for line in input.lines() {
+            let record = Record::from_line(line).unwrap();
+            assert_eq!(record.arrangements(), brute_force(&record), "{}", line);
+            assert_eq!(record.unfold(2).arrangements(), brute_force(&record.unfold(2)), "{}", line);
+        }
+
+        let mut rng = Lcg::new(7);
+        for _ in 0..300 {
+            let springs = (0..1 + rng.below(14)).map(|_| ".#?".as_bytes()[rng.below(3)] as char).collect::<String>();
+            let groups = (0..1 + rng.below(4)).map(|_| (1 + rng.below(3)).to_string()).collect::<Vec<_>>();
+            let line = format!("{} {}", springs, groups.join(","));
+            let record = Record::from_line(&line).unwrap();
+            assert_eq!(record.arrangements(), brute_force(&record), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_unfold() {
+        let record = Record::from_line(".# 1").unwrap().unfold(3);
+        assert_eq!(record.springs, b".#?.#?.#");
+        assert_eq!(record.groups, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_large_counts_fit_in_u64() {
+        // far more arrangements than fit in a u32
+        let record = Record::from_line("???????????????????? 1,1").unwrap().unfold(5);
+        assert!(record.arrangements() > u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Record::from_line("???.###").is_err());
+        assert!(Record::from_line("??x 1").is_err());
+        assert!(Record::from_line("??? 1,,1").is_err());
+        assert!(Record::from_line("??? 0").is_err());
+    }
+}
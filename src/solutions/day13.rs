@@ -0,0 +1,134 @@
+use std::fmt;
+
+use crate::bitgrid::BitGrid;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    /// A line between two rows
+    Horizontal,
+    /// A line between two columns
+    Vertical
+}
+
+/// A reflection line, `index` rows above it or columns left of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reflection {
+    pub axis: Axis,
+    pub index: usize
+}
+
+impl Reflection {
+    fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * self.index,
+            Axis::Vertical => self.index
+        }
+    }
+}
+
+impl fmt::Display for Reflection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.axis {
+            Axis::Horizontal => write!(f, "horizontal after row {}", self.index),
+            Axis::Vertical => write!(f, "vertical after column {}", self.index)
+        }
+    }
+}
+
+/// The first line after which the rows mirror each other with exactly `smudges` cells differing.
+/// Rows without a counterpart on the other side are ignored.
+fn mirror_row(grid: &BitGrid, smudges: usize) -> Option<usize> {
+    (1..grid.rows()).find(|&index| {
+        let mut differences = 0;
+        for (above, below) in (0..index).rev().zip(index..grid.rows()) {
+            differences += grid.row_diff(above, below);
+            if differences > smudges {
+                return false;
+            }
+        }
+        differences == smudges
+    })
+}
+
+fn find_reflection(grid: &BitGrid, smudges: usize) -> Option<Reflection> {
+    if let Some(index) = mirror_row(grid, smudges) {
+        return Some(Reflection { axis: Axis::Horizontal, index });
+    }
+    // columns of the grid are the rows of its transpose
+    mirror_row(&grid.transpose(), smudges).map(|index| Reflection { axis: Axis::Vertical, index })
+}
+
+/// The reflection of every blank-line-separated pattern, `None` where there is none
+pub fn reflections(input: &str, smudges: usize) -> Vec<Option<Reflection>> {
+    input.split("\n\n")
+        .map(|pattern| BitGrid::parse(pattern.trim(), '#'))
+        .map(|grid| find_reflection(&grid, smudges))
+        .collect()
+}
+
+fn solve(input: &str, smudges: usize) -> usize {
+    reflections(input, smudges).iter()
+        .enumerate()
+        .map(|(i, reflection)| match reflection {
+            Some(reflection) => reflection.summary(),
+            None => panic!("pattern {} has no reflection with {} smudges", i + 1, smudges)
+        })
+        .sum()
+}
+
+pub fn part_one(input: &str) -> usize {
+    solve(input, 0)
+}
+
+pub fn part_two(input: &str) -> usize {
+    solve(input, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::read_file;
+
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let input = read_file("examples", 13);
+        assert_eq!(part_one(&input), 405);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = read_file("examples", 13);
+        assert_eq!(part_two(&input), 400);
+    }
+
+    #[test]
+    fn test_reflections() {
+        let input = read_file("examples", 13);
+        assert_eq!(reflections(&input, 0), vec![
+            Some(Reflection { axis: Axis::Vertical, index: 5 }),
+            Some(Reflection { axis: Axis::Horizontal, index: 4 }),
+        ]);
+        assert_eq!(reflections(&input, 1), vec![
+            Some(Reflection { axis: Axis::Horizontal, index: 3 }),
+            Some(Reflection { axis: Axis::Horizontal, index: 1 }),
+        ]);
+        assert_eq!(reflections(&input, 0)[0].unwrap().to_string(), "vertical after column 5");
+    }
+
+    #[test]
+    fn test_smudge_count_is_exact() {
+        // mirrors perfectly after row 1, so with one smudge that line does not count
+        let pattern = "#.\n#.\n..";
+        assert_eq!(reflections(pattern, 0), vec![Some(Reflection { axis: Axis::Horizontal, index: 1 })]);
+        assert_eq!(reflections(pattern, 1), vec![Some(Reflection { axis: Axis::Horizontal, index: 2 })]);
+        assert_eq!(reflections(pattern, 2), vec![Some(Reflection { axis: Axis::Vertical, index: 1 })]);
+        assert_eq!(reflections(pattern, 3), vec![None]);
+    }
+
+    #[test]
+    #[should_panic(expected = "pattern 1 has no reflection with 0 smudges")]
+    fn test_no_reflection() {
+        part_one("#..\n..#\n.#.");
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+use crate::bitgrid::BitGrid;
+use crate::cycle;
+use crate::polygon::Direction;
+
+/// Round rocks roll, cube-shaped rocks stay put. One bit per cell for each,
+/// so the round rocks alone make a compact key for the platform's state.
+#[derive(Clone, Debug, PartialEq)]
+struct Platform {
+    round: BitGrid,
+    cube: BitGrid,
+}
+
+impl Platform {
+    fn parse(input: &str) -> Self {
+        Platform {
+            round: BitGrid::parse(input, 'O'),
+            cube: BitGrid::parse(input, '#'),
+        }
+    }
+
+    /// Rolls every round rock as far as it goes towards `direction`
+    fn tilt(&mut self, direction: Direction) {
+        let (rows, cols) = (self.round.rows(), self.round.cols());
+        let (lines, len) = match direction {
+            Direction::Up | Direction::Down => (cols, rows),
+            Direction::Left | Direction::Right => (rows, cols),
+        };
+        for line in 0..lines {
+            // the k-th cell of the line, counting from the edge the rocks roll towards
+            let cell = |k: usize| match direction {
+                Direction::Up => (k, line),
+                Direction::Down => (rows - 1 - k, line),
+                Direction::Left => (line, k),
+                Direction::Right => (line, cols - 1 - k),
+            };
+            let mut free = 0;
+            for k in 0..len {
+                let (r, c) = cell(k);
+                if self.cube.get(r, c) {
+                    free = k + 1;
+                } else if self.round.get(r, c) {
+                    self.round.set(r, c, false);
+                    let (r, c) = cell(free);
+                    self.round.set(r, c, true);
+                    free += 1;
+                }
+            }
+        }
+    }
+
+    /// Tilts north, west, south and east
+    fn spin(&mut self) {
+        for direction in [Direction::Up, Direction::Left, Direction::Down, Direction::Right] {
+            self.tilt(direction);
+        }
+    }
+
+    fn north_load(&self) -> usize {
+        self.round.iter_ones().map(|(r, _)| self.round.rows() - r).sum()
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for r in 0..self.round.rows() {
+            for c in 0..self.round.cols() {
+                let tile = match (self.round.get(r, c), self.cube.get(r, c)) {
+                    (true, _) => 'O',
+                    (_, true) => '#',
+                    _ => '.',
+                };
+                write!(f, "{}", tile)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn part_one(input: &str) -> usize {
+    let mut platform = Platform::parse(input);
+    platform.tilt(Direction::Up);
+    platform.north_load()
+}
+
+pub fn part_two(input: &str) -> usize {
+    let platform = Platform::parse(input);
+    cycle::nth_by_key(platform, Platform::spin, |platform| platform.round.clone(), 1_000_000_000).north_load()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::read_file;
+
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let input = read_file("examples", 14);
+        assert_eq!(part_one(&input), 136);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = read_file("examples", 14);
+        assert_eq!(part_two(&input), 64);
+    }
+
+    #[test]
+    fn test_tilt_north() {
+        let mut platform = Platform::parse(&read_file("examples", 14));
+        platform.tilt(Direction::Up);
+        let expected = "OOOO.#.O..\nOO..#....#\nOO..O##..O\nO..#.OO...\n........#.\n..#....#.#\n..O..#.O.O\n..O.......\n#....###..\n#....#....\n";
+        assert_eq!(platform.to_string(), expected);
+    }
+
+    #[test]
+    fn test_tilt_each_direction() {
+        let tilted = |direction| {
+            let mut platform = Platform::parse("O.#O.\n.O..O");
+            platform.tilt(direction);
+            platform.to_string()
+        };
+        assert_eq!(tilted(Direction::Up), "OO#OO\n.....\n");
+        assert_eq!(tilted(Direction::Down), "..#..\nOO.OO\n");
+        assert_eq!(tilted(Direction::Left), "O.#O.\nOO...\n");
+        assert_eq!(tilted(Direction::Right), ".O#.O\n...OO\n");
+    }
+
+    #[test]
+    fn test_spin() {
+        let mut platform = Platform::parse(&read_file("examples", 14));
+        platform.spin();
+        let expected = ".....#....\n....#...O#\n...OO##...\n.OO#......\n.....OOO#.\n.O#...O#.#\n....O#....\n......OOOO\n#...O###..\n#..OO#....\n";
+        assert_eq!(platform.to_string(), expected);
+        platform.spin();
+        platform.spin();
+        let expected = ".....#....\n....#...O#\n.....##...\n..O#......\n.....OOO#.\n.O#...O#.#\n....O#...O\n.......OOO\n#...O###.O\n#.OOO#...O\n";
+        assert_eq!(platform.to_string(), expected);
+    }
+
+    #[test]
+    fn test_fast_forward_matches_spinning() {
+        let start = Platform::parse(&read_file("examples", 14));
+        let mut platform = start.clone();
+        for n in 0..40 {
+            let skipped = cycle::nth_by_key(start.clone(), Platform::spin, |platform| platform.round.clone(), n);
+            assert_eq!(skipped, platform);
+            platform.spin();
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::error::Error;
+use std::fmt;
+
 fn hash_string(s: &str) -> u32 {
     let mut curr_val = 0;
     for c in s.chars() {
@@ -14,8 +17,208 @@ pub fn part_one(input: &str) -> u32 {
         .sum()
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Remove,
+    Insert(u32)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step<'a> {
+    pub label: &'a str,
+    pub operation: Operation
+}
+
+impl<'a> Step<'a> {
+    /// Parses `label-` or `label=focal_length`
+    pub fn parse(s: &'a str) -> Result<Self, Box<dyn Error>> {
+        if let Some(label) = s.strip_suffix('-') {
+            return Ok(Step { label, operation: Operation::Remove });
+        }
+        match s.split_once('=') {
+            Some((label, focal_length)) => {
+                let focal_length = focal_length.parse()
+                    .map_err(|e| format!("invalid focal length in step {:?}: {}", s, e))?;
+                Ok(Step { label, operation: Operation::Insert(focal_length) })
+            },
+            None => Err(format!("step {:?} has neither '-' nor '='", s).into())
+        }
+    }
+
+    /// The box the step works on
+    pub fn hash(&self) -> usize {
+        hash_string(self.label) as usize
+    }
+}
+
+impl fmt::Display for Step<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.operation {
+            Operation::Remove => write!(f, "{}-", self.label),
+            Operation::Insert(focal_length) => write!(f, "{}={}", self.label, focal_length)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lens {
+    pub label: String,
+    pub focal_length: u32
+}
+
+/// The 256 boxes, each holding its lenses in the order they were inserted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Boxes {
+    boxes: Vec<Vec<Lens>>
+}
+
+impl Boxes {
+    pub fn new() -> Self {
+        Boxes { boxes: vec![vec![]; 256] }
+    }
+
+    pub fn apply(&mut self, step: &Step) {
+        let lenses = &mut self.boxes[step.hash()];
+        let position = lenses.iter().position(|lens| lens.label == step.label);
+        match (step.operation, position) {
+            (Operation::Remove, Some(i)) => {
+                lenses.remove(i);
+            },
+            (Operation::Remove, None) => {},
+            (Operation::Insert(focal_length), Some(i)) => lenses[i].focal_length = focal_length,
+            (Operation::Insert(focal_length), None) => lenses.push(Lens {
+                label: step.label.to_string(),
+                focal_length
+            })
+        }
+    }
+
+    pub fn get(&self, i: usize) -> &[Lens] {
+        &self.boxes[i]
+    }
+
+    /// Indices and lenses of all boxes holding at least one lens
+    pub fn non_empty(&self) -> impl Iterator<Item = (usize, &[Lens])> {
+        self.boxes.iter()
+            .enumerate()
+            .filter(|(_, lenses)| !lenses.is_empty())
+            .map(|(i, lenses)| (i, lenses.as_slice()))
+    }
+
+    pub fn focusing_power(&self) -> u32 {
+        self.non_empty()
+            .flat_map(|(i, lenses)| lenses.iter().enumerate().map(move |(slot, lens)| {
+                (i as u32 + 1) * (slot as u32 + 1) * lens.focal_length
+            }))
+            .sum()
+    }
+}
+
+impl Default for Boxes {
+    fn default() -> Self {
+        Boxes::new()
+    }
+}
+
+fn steps(input: &str) -> impl Iterator<Item = Result<Step<'_>, Box<dyn Error>>> {
+    input.trim_end().split(",").map(Step::parse)
+}
+
+/// Runs every step of the initialization sequence and returns the final boxes
+pub fn arrange(input: &str) -> Result<Boxes, Box<dyn Error>> {
+    let mut boxes = Boxes::new();
+    for step in steps(input) {
+        boxes.apply(&step?);
+    }
+    Ok(boxes)
+}
+
+/// One step of the sequence together with the boxes right after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub step: String,
+    pub hash: usize,
+    pub boxes: Vec<(usize, Vec<Lens>)>
+}
+
+impl TraceEntry {
+    /// A single-line JSON object, so traces can be diffed line by line
+    pub fn to_json(&self) -> String {
+        let boxes = self.boxes.iter()
+            .map(|(i, lenses)| {
+                let lenses = lenses.iter()
+                    .map(|lens| format!("{{\"label\":{},\"focal_length\":{}}}", json_string(&lens.label), lens.focal_length))
+                    .collect::<Vec<_>>();
+                format!("{{\"box\":{},\"lenses\":[{}]}}", i, lenses.join(","))
+            })
+            .collect::<Vec<_>>();
+        format!("{{\"step\":{},\"hash\":{},\"boxes\":[{}]}}", json_string(&self.step), self.hash, boxes.join(","))
+    }
+}
+
+/// Same layout as the puzzle statement
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "After \"{}\" (box {}):", self.step, self.hash)?;
+        for (i, lenses) in &self.boxes {
+            write!(f, "Box {}:", i)?;
+            for lens in lenses {
+                write!(f, " [{} {}]", lens.label, lens.focal_length)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            c if c.is_control() => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Runs the sequence like `arrange`, recording the non-empty boxes after every step
+pub fn trace(input: &str) -> Result<Vec<TraceEntry>, Box<dyn Error>> {
+    let mut boxes = Boxes::new();
+    let mut entries = vec![];
+    for step in steps(input) {
+        let step = step?;
+        boxes.apply(&step);
+        entries.push(TraceEntry {
+            step: step.to_string(),
+            hash: step.hash(),
+            boxes: boxes.non_empty().map(|(i, lenses)| (i, lenses.to_vec())).collect()
+        });
+    }
+    Ok(entries)
+}
+
+/// The trace as text, or as a JSON array with one step per line
+pub fn explain(input: &str, json: bool) -> String {
+    let entries = match trace(input) {
+        Ok(entries) => entries,
+        Err(e) => panic!("{}", e)
+    };
+    if json {
+        let lines = entries.iter().map(TraceEntry::to_json).collect::<Vec<_>>();
+        return format!("[\n{}\n]\n", lines.join(",\n"));
+    }
+    entries.iter().map(|entry| entry.to_string()).collect::<Vec<_>>().join("\n")
+}
+
 pub fn part_two(input: &str) -> u32 {
-    0
+    match arrange(input) {
+        Ok(boxes) => boxes.focusing_power(),
+        Err(e) => panic!("{}", e)
+    }
 }
 
 #[cfg(test)]
@@ -33,7 +236,92 @@ mod tests {
 
     #[test]
     fn test_part_two() {
-        let input = read_file("examples", 25);
-        assert_eq!(part_two(&input), 0);
+        let input = read_file("inputs", 15);
+        assert_eq!(part_two(&input), 236057);
+    }
+
+    #[test]
+    fn test_examples() {
+        let input = read_file("examples", 15);
+        assert_eq!(part_one(&input), 1320);
+        assert_eq!(part_two(&input), 145);
+    }
+
+    fn contents(boxes: &Boxes, i: usize) -> Vec<(&str, u32)> {
+        boxes.get(i).iter().map(|lens| (lens.label.as_str(), lens.focal_length)).collect()
+    }
+
+    #[test]
+    fn test_intermediate_boxes() {
+        let input = read_file("examples", 15);
+        let steps = input.split(",").collect::<Vec<_>>();
+
+        // after "rn=1,cm-,qp=3"
+        let boxes = arrange(&steps[..3].join(",")).unwrap();
+        assert_eq!(contents(&boxes, 0), vec![("rn", 1)]);
+        assert_eq!(contents(&boxes, 1), vec![("qp", 3)]);
+
+        // after "...,cm=2,qp-"
+        let boxes = arrange(&steps[..5].join(",")).unwrap();
+        assert_eq!(contents(&boxes, 0), vec![("rn", 1), ("cm", 2)]);
+        assert!(boxes.get(1).is_empty());
+
+        let boxes = arrange(&input).unwrap();
+        assert_eq!(contents(&boxes, 0), vec![("rn", 1), ("cm", 2)]);
+        assert_eq!(contents(&boxes, 3), vec![("ot", 7), ("ab", 5), ("pc", 6)]);
+        assert_eq!(boxes.non_empty().count(), 2);
+    }
+
+    #[test]
+    fn test_insert_replaces_in_place() {
+        let boxes = arrange("ot=9,ab=5,ot=7").unwrap();
+        assert_eq!(contents(&boxes, 3), vec![("ot", 7), ("ab", 5)]);
+    }
+
+    #[test]
+    fn test_trace() {
+        let input = read_file("examples", 15);
+        let entries = trace(&input).unwrap();
+        assert_eq!(entries.len(), 11);
+        assert_eq!(entries[1].step, "cm-");
+        assert_eq!(entries[1].hash, 0);
+        assert_eq!(entries.last().unwrap().boxes, arrange(&input).unwrap().non_empty()
+            .map(|(i, lenses)| (i, lenses.to_vec()))
+            .collect::<Vec<_>>());
+
+        // qp- empties box 1
+        assert_eq!(entries[4].boxes.len(), 1);
+        assert_eq!(entries[4].to_string(), "After \"qp-\" (box 1):\nBox 0: [rn 1] [cm 2]\n");
+        assert_eq!(entries[10].to_string().lines().last(), Some("Box 3: [ot 7] [ab 5] [pc 6]"));
+    }
+
+    #[test]
+    fn test_explain() {
+        let input = read_file("examples", 15);
+        let text = explain(&input, false);
+        assert!(text.starts_with("After \"rn=1\" (box 0):\nBox 0: [rn 1]\n\nAfter \"cm-\""));
+
+        let json = explain(&input, true);
+        let lines = json.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "[");
+        assert_eq!(lines[1], r#"{"step":"rn=1","hash":0,"boxes":[{"box":0,"lenses":[{"label":"rn","focal_length":1}]}]},"#);
+        assert_eq!(lines[12], "]");
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("ab"), r#""ab""#);
+        assert_eq!(json_string("a\"b\\"), r#""a\"b\\""#);
+        assert_eq!(json_string("\n"), r#""\u000a""#);
+    }
+
+    #[test]
+    fn test_step_parse() {
+        assert_eq!(Step::parse("cm-").unwrap(), Step { label: "cm", operation: Operation::Remove });
+        assert_eq!(Step::parse("qp=3").unwrap(), Step { label: "qp", operation: Operation::Insert(3) });
+        assert_eq!(Step::parse("qp=3").unwrap().hash(), 1);
+        assert!(Step::parse("qp").is_err());
+        assert!(Step::parse("qp=x").is_err());
     }
 }